@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+
+use crate::collector::{
+    get_if_modified, parse_datetime, CollectedRelease, Collector, CollectorContext, CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+const CRATES_IO_INDEX: &str = "https://index.crates.io";
+
+/// one line of the index file
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    vers: String,
+    yanked: bool,
+}
+
+/// `config.json` at the root of the index
+#[derive(Debug, Deserialize)]
+struct IndexConfig {
+    api: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateVersion {
+    num: String,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateVersionsMeta {
+    next_page: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateVersions {
+    versions: Vec<CrateVersion>,
+    meta: Option<CrateVersionsMeta>,
+}
+
+/// collector for cargo registries with sparse index (crates.io, ...)
+pub struct CratesIoCollector {
+    project_name: String,
+    index_url: String,
+    crate_name: String,
+}
+
+/// path of the crate in the index (`se/rd/serde`)
+fn index_path(crate_name: &str) -> String {
+    let name = crate_name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+
+impl CratesIoCollector {
+    pub fn new(project_name: &str, index_url: &str, crate_name: &str) -> Self {
+        Self {
+            project_name: project_name.to_string(),
+            index_url: index_url.trim_start_matches("sparse+").to_string(),
+            crate_name: crate_name.to_string(),
+        }
+    }
+
+    /// `source = { crate = "serde" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let crate_name = source.crate_name.as_deref().unwrap_or_default();
+        if crate_name.is_empty() {
+            return Err(CollectorError::Config("empty crate name".to_string()));
+        }
+        // index path is sliced by bytes, crate names are ascii alphanumerics, `-` and `_`
+        if !crate_name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(CollectorError::Config(format!("invalid crate name: {}", crate_name)));
+        }
+        let index_url = ctx.config.registry_url(source, CRATES_IO_INDEX);
+        Ok(Box::new(Self::new(project_name, &index_url, crate_name)))
+    }
+
+    /// `None` when the index file is not modified since the last check
+    async fn get_index_entries(&self, ctx: &CollectorContext<'_>) -> Result<Option<Vec<IndexEntry>>, CollectorError> {
+        let url = format!("{}/{}", self.index_url, index_path(&self.crate_name));
+        let body = match get_if_modified(ctx, &self.project_name, &url, &HeaderMap::new()).await? {
+            Some(res) => res.text().await?,
+            None => return Ok(None),
+        };
+        body.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).map_err(|e| CollectorError::Parse(format!("index entry: {}", e))))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    /// web API url of the registry, crates.io is `https://crates.io`
+    async fn get_api_url(&self, ctx: &CollectorContext<'_>) -> Option<String> {
+        let url = format!("{}/config.json", self.index_url);
+        let res = ctx.client.get(url).send().await.ok()?.error_for_status().ok()?;
+        let config: IndexConfig = res.json().await.ok()?;
+        config.api.map(|s| s.trim_end_matches('/').to_string())
+    }
+
+    /// publish date of each version
+    async fn get_publish_dates(
+        &self,
+        ctx: &CollectorContext<'_>,
+        api_url: &str,
+    ) -> Result<HashMap<String, String>, CollectorError> {
+        let versions_url = format!("{}/api/v1/crates/{}/versions", api_url, self.crate_name);
+        let mut dates = HashMap::new();
+        let mut next_url = Some(versions_url.clone());
+        while let Some(url) = next_url {
+            debug!("GET {}", url);
+            let res: CrateVersions = ctx.client.get(url).send().await?.error_for_status()?.json().await?;
+            next_url = res
+                .meta
+                .and_then(|m| m.next_page)
+                .map(|query| format!("{}{}", versions_url, query));
+            for v in res.versions {
+                dates.insert(v.num, v.created_at);
+            }
+        }
+        Ok(dates)
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for CratesIoCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let entries = match self.get_index_entries(ctx).await? {
+            Some(entries) => entries,
+            None => return Ok(vec![]),
+        };
+        debug!("crates_io.index: {:#?}", entries);
+
+        let api_url = self.get_api_url(ctx).await;
+        let dates = match &api_url {
+            Some(api_url) => self.get_publish_dates(ctx, api_url).await?,
+            None => HashMap::new(),
+        };
+
+        let mut releases = vec![];
+        for entry in entries.iter() {
+            let bump_date = match dates.get(&entry.vers) {
+                Some(date) => parse_datetime(date)?,
+                None => {
+                    warn!("not found publish date: {} {}", self.crate_name, entry.vers);
+                    Utc::now().naive_utc()
+                }
+            };
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version: entry.vers.clone(),
+                bump_date,
+                url: api_url
+                    .as_ref()
+                    .map(|api_url| format!("{}/crates/{}/{}", api_url, self.crate_name, entry.vers)),
+                yanked: entry.yanked,
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+}
@@ -0,0 +1,269 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+use reqwest::header::HeaderMap;
+
+use crate::collector::{
+    compile_version_regex, get_if_modified, match_version, CollectedRelease, Collector, CollectorContext,
+    CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+/// `<item>` of RSS 2.0 or `<entry>` of Atom
+#[derive(Debug, Default)]
+struct FeedEntry {
+    title: String,
+    link: Option<String>,
+    published: Option<String>,
+    updated: Option<String>,
+}
+
+impl FeedEntry {
+    fn date(&self) -> Option<NaiveDateTime> {
+        let date = self.published.as_ref().or(self.updated.as_ref())?;
+        DateTime::parse_from_rfc3339(date.trim())
+            .or_else(|_| DateTime::parse_from_rfc2822(date.trim()))
+            .ok()
+            .map(|d| d.naive_utc())
+    }
+}
+
+/// href of Atom `<link>` except for non alternate links (`rel="self"`, `rel="enclosure"`, ...)
+fn atom_link(e: &BytesStart) -> Option<String> {
+    let mut href = None;
+    for attr in e.attributes().flatten() {
+        let value = attr.unescape_value().ok()?.to_string();
+        match attr.key.local_name().as_ref() {
+            b"href" => href = Some(value),
+            b"rel" if value != "alternate" => return None,
+            _ => {}
+        }
+    }
+    href
+}
+
+fn parse_feed(xml: &str) -> Result<Vec<FeedEntry>, CollectorError> {
+    // text is not trimmed by the reader, the spaces around CDATA are a part of the title
+    let mut reader = Reader::from_str(xml);
+
+    let mut entries = vec![];
+    let mut entry: Option<FeedEntry> = None;
+    let mut element = String::new();
+    loop {
+        let text = match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "item" | "entry" => entry = Some(FeedEntry::default()),
+                    "link" => {
+                        if let Some(entry) = entry.as_mut().filter(|entry| entry.link.is_none()) {
+                            entry.link = atom_link(&e);
+                        }
+                    }
+                    _ => {}
+                }
+                element = name;
+                continue;
+            }
+            Ok(Event::Empty(e)) => {
+                if e.local_name().as_ref() == b"link" {
+                    if let Some(entry) = entry.as_mut().filter(|entry| entry.link.is_none()) {
+                        entry.link = atom_link(&e);
+                    }
+                }
+                continue;
+            }
+            Ok(Event::End(e)) => {
+                if matches!(e.local_name().as_ref(), b"item" | b"entry") {
+                    if let Some(mut entry) = entry.take() {
+                        entry.title = entry.title.trim().to_string();
+                        entries.push(entry);
+                    }
+                }
+                element.clear();
+                continue;
+            }
+            Ok(Event::Text(e)) => e
+                .unescape()
+                .map_err(|e| CollectorError::Parse(format!("feed: {}", e)))?
+                .to_string(),
+            Ok(Event::CData(e)) => String::from_utf8_lossy(&e.into_inner()).to_string(),
+            Ok(Event::Eof) => break,
+            Ok(_) => continue,
+            Err(e) => return Err(CollectorError::Parse(format!("feed: {}", e))),
+        };
+
+        let entry = match entry.as_mut() {
+            Some(entry) => entry,
+            None => continue,
+        };
+        match element.as_str() {
+            "title" => entry.title.push_str(&text),
+            "link" if entry.link.is_none() => entry.link = Some(text.trim().to_string()),
+            "pubDate" | "published" | "issued" => entry.published = Some(text.trim().to_string()),
+            "updated" | "date" | "modified" => entry.updated = Some(text.trim().to_string()),
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+/// collector for RSS 2.0 and Atom feeds, `version_regex` extracts the version from entry title or link
+pub struct FeedCollector {
+    project_name: String,
+    feed_url: String,
+    version_regex: Option<Regex>,
+}
+
+impl FeedCollector {
+    pub fn new(project_name: &str, feed_url: &str, version_regex: Option<Regex>) -> Self {
+        Self {
+            project_name: project_name.to_string(),
+            feed_url: feed_url.to_string(),
+            version_regex,
+        }
+    }
+
+    /// `source = { feed = "https://example.com/releases.atom" }`
+    pub fn factory(
+        _ctx: &CollectorContext,
+        project_name: &str,
+        project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let feed_url = source.feed.as_deref().unwrap_or_default();
+        if feed_url.is_empty() {
+            return Err(CollectorError::Config("empty feed url".to_string()));
+        }
+        let version_regex = compile_version_regex(project)?;
+        if version_regex.is_none() {
+            return Err(CollectorError::Config("feed source requires version_regex".to_string()));
+        }
+        Ok(Box::new(Self::new(project_name, feed_url, version_regex)))
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for FeedCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let xml = match get_if_modified(ctx, &self.project_name, &self.feed_url, &HeaderMap::new()).await? {
+            Some(res) => res.text().await?,
+            None => return Ok(vec![]),
+        };
+        let entries = parse_feed(&xml)?;
+        debug!("feed.entries: {:#?}", entries);
+
+        let mut releases = vec![];
+        for entry in entries.iter() {
+            let version = match match_version(&self.version_regex, &entry.title).or_else(|| {
+                entry
+                    .link
+                    .as_ref()
+                    .and_then(|link| match_version(&self.version_regex, link))
+            }) {
+                Some(v) if !v.is_empty() => v,
+                _ => {
+                    debug!("not found version: {}", entry.title);
+                    continue;
+                }
+            };
+            let bump_date = match entry.date() {
+                Some(d) => d,
+                None => {
+                    warn!("not found entry date: {}", entry.title);
+                    Utc::now().naive_utc()
+                }
+            };
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version,
+                bump_date,
+                url: entry.link.clone(),
+                yanked: false,
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">
+  <channel>
+    <title>Example releases</title>
+    <link>https://example.com/</link>
+    <atom:link href="https://example.com/feed.xml" rel="self" type="application/rss+xml"/>
+    <item>
+      <title><![CDATA[Release 1.2.0]]></title>
+      <link>https://example.com/releases/1.2.0</link>
+      <pubDate>Tue, 02 Jan 2024 03:04:05 +0900</pubDate>
+    </item>
+    <item>
+      <title>
+        Release <![CDATA[1.1.0]]> &amp; fixes
+      </title>
+      <link>https://example.com/releases/1.1.0</link>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example releases</title>
+  <link href="https://example.com/releases.atom" rel="self"/>
+  <entry>
+    <title>v2.0.0</title>
+    <link rel="self" href="https://example.com/entries/2.0.0.atom"/>
+    <link rel="alternate" type="text/html" href="https://example.com/releases/v2.0.0"/>
+    <published>2024-03-01T00:00:00Z</published>
+    <updated>2024-03-05T12:00:00+02:00</updated>
+  </entry>
+  <entry>
+    <title type="html">v1.9.0</title>
+    <link href="https://example.com/releases/v1.9.0"/>
+    <updated>2024-02-01T10:00:00+02:00</updated>
+  </entry>
+</feed>"#;
+
+    fn date(s: &str) -> NaiveDateTime {
+        DateTime::parse_from_rfc3339(s).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn parse_rss() {
+        let entries = parse_feed(RSS).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].title, "Release 1.2.0");
+        assert_eq!(entries[0].link.as_deref(), Some("https://example.com/releases/1.2.0"));
+        assert_eq!(entries[0].date(), Some(date("2024-01-01T18:04:05Z")));
+
+        assert_eq!(entries[1].title, "Release 1.1.0 & fixes");
+        assert_eq!(entries[1].link.as_deref(), Some("https://example.com/releases/1.1.0"));
+        assert_eq!(entries[1].date(), None);
+    }
+
+    #[test]
+    fn parse_atom() {
+        let entries = parse_feed(ATOM).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        // the `self` link is skipped for the `alternate` one, `published` is used before `updated`
+        assert_eq!(entries[0].title, "v2.0.0");
+        assert_eq!(entries[0].link.as_deref(), Some("https://example.com/releases/v2.0.0"));
+        assert_eq!(entries[0].date(), Some(date("2024-03-01T00:00:00Z")));
+
+        // a link without `rel` is alternate, `updated` is the date without `published`
+        assert_eq!(entries[1].title, "v1.9.0");
+        assert_eq!(entries[1].link.as_deref(), Some("https://example.com/releases/v1.9.0"));
+        assert_eq!(entries[1].date(), Some(date("2024-02-01T08:00:00Z")));
+    }
+}
@@ -1,80 +1,311 @@
-use chrono::NaiveDateTime;
-use git2::build::RepoBuilder;
-use git2::{Cred, FetchOptions, RemoteCallbacks, Repository};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{
+    AutotagOption, Cred, CredentialType, DiffStats, Direction, FetchOptions, FetchPrune, Oid, Reference, Remote,
+    RemoteCallbacks, Repository, Sort,
+};
 use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::io::prelude::*;
-use std::io::BufReader;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
 
-use crate::database;
+use crate::collector::signature::{split_signature, SignatureVerifier};
+use crate::collector::{
+    compile_version_regex, match_version, release_notes, ChangeSummary, CollectedRelease, Collector, CollectorContext,
+    CollectorError, SignatureStatus, TagSignature,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+use crate::version::{self, VersionScheme};
 
 lazy_static! {
     static ref RE_GIT_DIR: Regex = Regex::new(r"^(https://|git@)(.*).git$").unwrap();
-    static ref RE_GIT_TAG: Regex = Regex::new(r"tag: (.*)(, .*)?").unwrap();
 }
 
-#[derive(Debug, Deserialize)]
-struct GitInfo {
-    tag: String,
-    date: String,
-    #[allow(dead_code)]
-    hash: String,  // not use now
+/// how the repository is kept under `rootdir`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum GitMode {
+    /// full clone with the branch checked out
+    #[default]
+    Clone,
+    /// bare repository with only the tips of the branch and the tags (depth 1 over http)
+    Bare,
+    /// no local repository, tags are listed like `git ls-remote --tags`
+    Remote,
+}
+
+impl GitMode {
+    fn parse(mode: Option<&str>) -> Result<Self, CollectorError> {
+        match mode {
+            None | Some("clone") => Ok(GitMode::Clone),
+            Some("bare") => Ok(GitMode::Bare),
+            Some("remote") => Ok(GitMode::Remote),
+            Some(mode) => Err(CollectorError::Config(format!("unknown git_mode: {}", mode))),
+        }
+    }
+}
+
+/// way to authenticate to the remote, tried in this order
+#[derive(Debug, Clone, Copy)]
+enum CredentialMethod {
+    SshKey,
+    SshAgent,
+    Token,
+    Netrc,
+    CredentialHelper,
+}
+
+/// credentials of the remote, from the project source or the global config
+#[derive(Debug, Default)]
+struct GitCredentials {
+    ssh_key: Option<String>,
+    /// environment variable name of the passphrase of `ssh_key`
+    ssh_key_passphrase_env: Option<String>,
+    username: Option<String>,
+    token: Option<String>,
+}
+
+impl GitCredentials {
+    fn from_config(ctx: &CollectorContext, source: &ProjectSourceConfig) -> Self {
+        match &source.ssh_key {
+            Some(key) => Self {
+                ssh_key: Some(key.clone()),
+                ssh_key_passphrase_env: source.ssh_key_passphrase_env.clone(),
+                username: source.username.clone(),
+                token: source.token.clone(),
+            },
+            None => Self {
+                ssh_key: ctx.config.git_ssh_key.clone(),
+                ssh_key_passphrase_env: source
+                    .ssh_key_passphrase_env
+                    .clone()
+                    .or_else(|| ctx.config.git_ssh_key_passphrase_env.clone()),
+                username: source.username.clone(),
+                token: source.token.clone(),
+            },
+        }
+    }
+
+    /// libgit2 asks the credential again after each failure, so every call tries the next method
+    fn callbacks(&self) -> RemoteCallbacks<'_> {
+        let mut callbacks = RemoteCallbacks::new();
+        let mut tried = 0;
+        callbacks.credentials(move |url, username_from_url, allowed| {
+            let username = self.username.as_deref().or(username_from_url).unwrap_or("git");
+            if allowed.contains(CredentialType::USERNAME) {
+                return Cred::username(username);
+            }
+
+            let methods: &[CredentialMethod] = if allowed.contains(CredentialType::SSH_KEY) {
+                &[CredentialMethod::SshKey, CredentialMethod::SshAgent]
+            } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+                &[
+                    CredentialMethod::Token,
+                    CredentialMethod::Netrc,
+                    CredentialMethod::CredentialHelper,
+                ]
+            } else {
+                &[]
+            };
+            while tried < methods.len() {
+                let method = methods[tried];
+                tried += 1;
+                if let Some(cred) = self.credential(method, url, username) {
+                    debug!("git credential: {:?}", method);
+                    return Ok(cred);
+                }
+            }
+            Err(git2::Error::from_str(&format!("no more credentials for {}", url)))
+        });
+        callbacks
+    }
+
+    fn credential(&self, method: CredentialMethod, url: &str, username: &str) -> Option<Cred> {
+        match method {
+            CredentialMethod::SshKey => {
+                let key = self.ssh_key.as_ref()?;
+                let pubkey_path = PathBuf::from(format!("{}.pub", key));
+                let passphrase = self
+                    .ssh_key_passphrase_env
+                    .as_ref()
+                    .and_then(|name| match env::var(name) {
+                        Ok(passphrase) => Some(passphrase),
+                        Err(_) => {
+                            warn!("not found ssh key passphrase in environment variable: {}", name);
+                            None
+                        }
+                    });
+                Cred::ssh_key(
+                    username,
+                    Some(pubkey_path.as_path()).filter(|p| p.exists()),
+                    Path::new(key),
+                    passphrase.as_deref(),
+                )
+                .ok()
+            }
+            CredentialMethod::SshAgent => Cred::ssh_key_from_agent(username).ok(),
+            CredentialMethod::Token => {
+                let token = self.token.as_ref()?;
+                Cred::userpass_plaintext(username, token).ok()
+            }
+            CredentialMethod::Netrc => {
+                let host = Url::parse(url).ok()?.host_str()?.to_string();
+                let (login, password) = netrc_credential(&host)?;
+                Cred::userpass_plaintext(&login, &password).ok()
+            }
+            CredentialMethod::CredentialHelper => {
+                let config = git2::Config::open_default().ok()?;
+                Cred::credential_helper(&config, url, self.username.as_deref()).ok()
+            }
+        }
+    }
+}
+
+/// (login, password) of the host in `$NETRC` or `~/.netrc`
+fn netrc_credential(host: &str) -> Option<(String, String)> {
+    let path = match env::var_os("NETRC") {
+        Some(path) => PathBuf::from(path),
+        None => dirs::home_dir()?.join(".netrc"),
+    };
+    parse_netrc(&fs::read_to_string(path).ok()?, host)
+}
+
+/// (login, password) of the `machine` entry of the host, or of the `default` entry which is the last one
+fn parse_netrc(netrc: &str, host: &str) -> Option<(String, String)> {
+    let mut tokens = netrc.split_whitespace();
+    let mut in_entry = false;
+    let (mut login, mut password) = (None, None);
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => {
+                if in_entry {
+                    break;
+                }
+                in_entry = tokens.next() == Some(host);
+            }
+            "default" => {
+                if in_entry {
+                    break;
+                }
+                in_entry = true;
+            }
+            // the value of the other entries is skipped too, it may be a keyword (`password default`)
+            "login" | "password" | "account" => {
+                let value = tokens.next().map(|s| s.to_string());
+                match token {
+                    "login" if in_entry => login = value,
+                    "password" if in_entry => password = value,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Some((login?, password?))
 }
 
 #[derive(Debug, Default)]
 pub struct GitCollector {
-    db_url: String,
+    project_name: String,
     clone_url: String,
     url: String,
-    project_name: String,
     branch: String,
     directory: String,
     version_regex: Option<Regex>,
-    ssh_key: Option<String>,
+    credentials: GitCredentials,
+    reachable_only: bool,
+    /// patterns of `branches`, each matched branch is a channel
+    branches: Vec<Regex>,
+    mode: GitMode,
+    verifier: SignatureVerifier,
+    /// order of the versions to find the previous version, the date order without it
+    version_scheme: Option<VersionScheme>,
 }
 
-fn git_clone(url: &str, directory: &str, ssh_key: &Option<String>) -> Result<Repository, git2::Error> {
-    match ssh_key {
-        Some(key) => {
-            let mut builder = RepoBuilder::new();
-            let mut callbacks = RemoteCallbacks::new();
-            let mut fetch_options = FetchOptions::new();
-
-            callbacks.credentials(|_, _, _| {
-                let pubkey_path = format!("{}.pub", key);
-                let privatekey_path = key.to_string();
-                let credentials = Cred::ssh_key(
-                    "git",
-                    Some(Path::new(&pubkey_path)),
-                    Path::new(privatekey_path.as_str()),
-                    None,
-                )
-                .expect("fail crate credentials");
-                Ok(credentials)
-            });
-            fetch_options.remote_callbacks(callbacks);
-            builder.fetch_options(fetch_options);
+/// branch name pattern, `*` matches any string and `?` any character (`release-*`)
+fn branch_pattern(glob: &str) -> Regex {
+    let pattern = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+    Regex::new(&format!("^{}$", pattern)).expect("invalid branch pattern")
+}
 
-            info!("git clone {}", url);
-            builder.clone(url, Path::new(directory))
-        }
-        None => Repository::clone(url, directory),
+/// tagger date of annotated tag, commit date of lightweight tag
+fn tag_date(repo: &Repository, reference: &Reference) -> Result<NaiveDateTime, CollectorError> {
+    let tagger_time = reference
+        .target()
+        .and_then(|oid| repo.find_tag(oid).ok())
+        .and_then(|tag| tag.tagger().map(|t| t.when().seconds()));
+    let seconds = match tagger_time {
+        Some(seconds) => seconds,
+        None => reference.peel_to_commit()?.time().seconds(),
+    };
+    DateTime::from_timestamp(seconds, 0)
+        .map(|d| d.naive_utc())
+        .ok_or_else(|| CollectorError::Parse(format!("invalid git time: {}", seconds)))
+}
+
+/// message of the annotated tag, `None` for lightweight tags
+fn tag_message(repo: &Repository, reference: &Reference) -> Option<String> {
+    let tag = repo.find_tag(reference.target()?).ok()?;
+    release_notes(tag.message().map(|m| split_signature(m).0))
+}
+
+/// commit summaries grouped by author
+pub type Shortlog = Vec<(String, Vec<String>)>;
+
+/// one commit of the shortlog
+struct CommitLog {
+    author: String,
+    email: String,
+    summary: String,
+}
+
+/// commits reachable from `to` and not from `from`, oldest first. authors are mapped with `.mailmap`.
+fn commits_between(repo: &Repository, from: Oid, to: Oid) -> Result<Vec<CommitLog>, CollectorError> {
+    let mailmap = repo.mailmap()?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    revwalk.push(to)?;
+    revwalk.hide(from)?;
+
+    let mut commits = vec![];
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let author = commit.author_with_mailmap(&mailmap)?;
+        commits.push(CommitLog {
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+        });
+    }
+    Ok(commits)
+}
+
+/// `git diff --shortstat from to`
+fn diffstat(repo: &Repository, from: Oid, to: Oid) -> Result<DiffStats, CollectorError> {
+    let from_tree = repo.find_commit(from)?.tree()?;
+    let to_tree = repo.find_commit(to)?.tree()?;
+    Ok(repo
+        .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)?
+        .stats()?)
+}
+
+fn change_summary(previous_version: &str, commits: &[CommitLog], stats: &DiffStats) -> ChangeSummary {
+    let contributors: HashSet<&str> = commits.iter().map(|c| c.email.as_str()).collect();
+    ChangeSummary {
+        previous_version: previous_version.to_string(),
+        commits: commits.len(),
+        contributors: contributors.len(),
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
     }
 }
 
 impl GitCollector {
-    pub fn new(
-        db_url: &str,
-        rootdir: &str,
-        clone_url: &str,
-        project_name: &str,
-        url: &str,
-        branch: &str,
-        version_regex: &Option<String>,
-        ssh_key: Option<String>,
-    ) -> Self {
+    pub fn new(rootdir: &str, clone_url: &str, url: &str, branch: &str, version_regex: Option<Regex>) -> Self {
         let git_directory = match RE_GIT_DIR.captures(clone_url) {
             Some(caps) => {
                 let directory = caps.get(2).unwrap();
@@ -82,167 +313,461 @@ impl GitCollector {
             }
             None => "".to_string(),
         };
-        let re_version = version_regex.as_ref().map(|s| Regex::new(s.as_str()).unwrap());
 
         Self {
-            db_url: db_url.to_string(),
             clone_url: clone_url.to_string(),
             url: url.to_string(),
-            project_name: project_name.to_string(),
             branch: branch.to_string(),
             directory: git_directory,
-            version_regex: re_version,
-            ssh_key,
+            version_regex,
+            ..Default::default()
         }
     }
 
-    pub fn init(&mut self) {
-        let old_curdir = env::current_dir().unwrap();
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        Ok(Box::new(Self::from_config(ctx, project_name, project, source)?))
+    }
 
-        if self.directory.is_empty() {
-            // TODO: error handling
-            error!("not found git repo directory");
-            return;
+    pub fn from_config(
+        ctx: &CollectorContext,
+        project_name: &str,
+        project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Self, CollectorError> {
+        let branch = match &source.branch {
+            Some(b) => b.as_str(),
+            None => "master",
+        };
+        let version_regex = compile_version_regex(project)?;
+        let mode = GitMode::parse(source.git_mode.as_deref())?;
+        let reachable_only = source.reachable_only.unwrap_or(false);
+        let branches: Vec<Regex> = source.branches.iter().flatten().map(|b| branch_pattern(b)).collect();
+        if (reachable_only || !branches.is_empty()) && mode != GitMode::Clone {
+            return Err(CollectorError::Config(
+                "reachable_only and branches require git_mode = \"clone\"".to_string(),
+            ));
         }
-
-        let _repo = match Repository::open(&self.directory) {
-            Ok(repo) => repo,
-            Err(_) => match git_clone(&self.clone_url, &self.directory, &self.ssh_key) {
-                Ok(repo) => repo,
-                Err(e) => panic!("fail git clone. error: {:?}", e),
-            },
+        let verifier = SignatureVerifier {
+            gpg_keyring: source.gpg_keyring.clone(),
+            allowed_signers: source.allowed_signers.clone(),
         };
-
-        if env::set_current_dir(&self.directory).is_err() {
-            return;
+        if verifier.is_enabled() && mode == GitMode::Remote {
+            return Err(CollectorError::Config(
+                "gpg_keyring and allowed_signers require the tags in local repository".to_string(),
+            ));
+        }
+        for path in [&verifier.gpg_keyring, &verifier.allowed_signers].into_iter().flatten() {
+            if !Path::new(path).exists() {
+                return Err(CollectorError::Config(format!("not found: {}", path)));
+            }
         }
+        Ok(Self {
+            project_name: project_name.to_string(),
+            credentials: GitCredentials::from_config(ctx, source),
+            reachable_only,
+            branches,
+            mode,
+            verifier,
+            version_scheme: project.version_scheme,
+            ..Self::new(
+                ctx.config.rootdir.to_str().unwrap(),
+                source.git.as_deref().unwrap_or_default(),
+                &project.url,
+                branch,
+                version_regex,
+            )
+        })
+    }
 
-        // TODO: use git2-rs
+    fn fetch_options(&self) -> FetchOptions<'_> {
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(self.credentials.callbacks());
+        fetch_options
+    }
 
-        // set branch
-        let git_branch = &self.branch;
-        debug!("repo: {}, branch: {}", self.url, git_branch);
-        let _proc = Command::new("git")
-            .arg("checkout")
-            .arg(git_branch)
-            .output()
-            .expect("fail git checkout command");
+    fn repository_directory(&self) -> String {
+        match self.mode {
+            GitMode::Bare => format!("{}.git", self.directory),
+            _ => self.directory.clone(),
+        }
+    }
 
-        // fetch --prune
-        let _proc = Command::new("git")
-            .arg("fetch")
-            .arg("--prune")
-            .output()
-            .expect("fail git fetch --prune command");
+    /// open the local repository, clone it at the first time
+    fn open_repository(&self) -> Result<Repository, CollectorError> {
+        if self.directory.is_empty() {
+            return Err(CollectorError::Config(format!(
+                "not found git repo directory: {}",
+                self.clone_url
+            )));
+        }
 
-        // pull
-        let _proc = Command::new("git").arg("pull").output().expect("fail git pull command");
+        if self.mode == GitMode::Bare {
+            let directory = self.repository_directory();
+            return match Repository::open_bare(&directory) {
+                Ok(repo) => Ok(repo),
+                Err(_) => {
+                    info!("git init --bare {}", directory);
+                    let repo = Repository::init_bare(&directory)?;
+                    repo.remote("origin", &self.clone_url)?;
+                    Ok(repo)
+                }
+            };
+        }
 
-        if env::set_current_dir(old_curdir).is_err() {
-            error!("fail to set current dir")
+        match Repository::open(&self.directory) {
+            Ok(repo) => Ok(repo),
+            Err(_) => {
+                info!("git clone {}", self.clone_url);
+                let mut builder = RepoBuilder::new();
+                builder.fetch_options(self.fetch_options());
+                Ok(builder.clone(&self.clone_url, Path::new(&self.directory))?)
+            }
         }
     }
 
-    pub fn collect(self) -> usize {
-        let old_curdir = env::current_dir().unwrap();
+    /// `git fetch --prune --tags`, then check out the branch at the fetched commit.
+    /// bare mirror fetches only the tips of the branch and the tags.
+    /// with `branches`, the remote branches are used as they are and nothing is checked out.
+    fn update(&self, repo: &Repository) -> Result<(), CollectorError> {
+        debug!("repo: {}, branch: {}", self.url, self.branch);
+        let mut remote = repo.find_remote("origin")?;
+        let mut options = self.fetch_options();
+        options.prune(FetchPrune::On).download_tags(AutotagOption::All);
+        let branch_ref = format!("refs/heads/{}", self.branch);
+        let remote_branch_ref = format!("refs/remotes/origin/{}", self.branch);
+        if self.mode == GitMode::Bare {
+            // libgit2 breaks shallow negotiation of stateful (ssh, git) transports
+            if self.clone_url.starts_with("https://") || self.clone_url.starts_with("http://") {
+                options.depth(1);
+            }
+            let refspecs = [
+                format!("+{}:{}", branch_ref, remote_branch_ref),
+                "+refs/tags/*:refs/tags/*".to_string(),
+            ];
+            remote.fetch(&refspecs, Some(&mut options), None)?;
+        } else {
+            remote.fetch::<&str>(&[], Some(&mut options), None)?;
+        }
+        if !self.branches.is_empty() {
+            return Ok(());
+        }
 
-        if env::set_current_dir(&self.directory).is_err() {
-            return 0;
+        let commit = repo.find_reference(&remote_branch_ref)?.peel_to_commit()?;
+        repo.reference(&branch_ref, commit.id(), true, "tamatebako: update branch")?;
+        repo.set_head(&branch_ref)?;
+        if !repo.is_bare() {
+            repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
         }
-        let mut dbconn = database::get_database_connection(self.db_url.as_str());
+        Ok(())
+    }
 
-        let mut git_proc = Command::new("git")
-            .arg("log")
-            .arg("-n300")
-            .arg("--oneline")
-            .arg("--date=format:%Y/%m/%d %H:%M:%S")
-            .arg("--pretty=format:%D %s\t%cd\t%H")
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("fail git log command");
+    /// version of the tag. `version_regex` is matched against the tag name,
+    /// and against `tag: <name>` for the regex written for `git log` decorations.
+    fn tag_version(&self, name: &str) -> Option<String> {
+        match_version(&self.version_regex, name)
+            .or_else(|| match_version(&self.version_regex, &format!("tag: {}", name)))
+            .filter(|v| !v.is_empty())
+    }
 
-        match git_proc.wait() {
-            Ok(out) => {
-                if !out.success() {
-                    panic!("fail git command");
-                }
-            }
-            Err(e) => panic!("fail git command. {:?}", e),
+    /// tags listed by the remote without local repository.
+    /// the remote does not tell the dates, so the first found time is used as the date.
+    fn collect_remote_releases(&self) -> Result<Vec<CollectedRelease>, CollectorError> {
+        debug!("ls-remote {}", self.clone_url);
+        let mut remote = Remote::create_detached(self.clone_url.as_str())?;
+        let connection = remote.connect_auth(Direction::Fetch, Some(self.credentials.callbacks()), None)?;
+
+        let now = Utc::now().naive_utc();
+        let mut releases = vec![];
+        for head in connection.list()? {
+            // `refs/tags/<name>^{}` is the commit of the annotated tag
+            let name = match head.name().strip_prefix("refs/tags/") {
+                Some(name) if !name.ends_with("^{}") => name,
+                _ => continue,
+            };
+            let version = match self.tag_version(name) {
+                Some(v) => v,
+                None => continue,
+            };
+            releases.push(CollectedRelease {
+                channel: self.branch.clone(),
+                version: version.clone(),
+                bump_date: now,
+                url: Some(format!("{}/releases/tag/{}", self.url, version)),
+                yanked: false,
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
         }
+        // all tags have the same date, the latest version is the last stored one
+        releases.sort_by(|a, b| version::compare(None, &a.version, &b.version));
+        Ok(releases)
+    }
 
-        let mut s = String::new();
-        if let Some(ref mut stdout) = git_proc.stdout {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines() {
-                let l = line.unwrap();
-                match &self.version_regex {
-                    Some(vregex) => {
-                        if vregex.is_match(l.as_str()) {
-                            s.push_str(l.as_str());
-                            s.push('\n');
-                        }
-                    }
-                    None => {}
-                }
+    /// (channel, tip) of the remote branches matched with `branches`
+    fn matched_branches(&self, repo: &Repository) -> Result<Vec<(String, Oid)>, CollectorError> {
+        let mut branches = vec![];
+        for reference in repo.references_glob("refs/remotes/origin/*")? {
+            let reference = reference?;
+            let name = match reference.name().and_then(|n| n.strip_prefix("refs/remotes/origin/")) {
+                Some(name) if name != "HEAD" => name.to_string(),
+                _ => continue,
+            };
+            if self.branches.iter().any(|b| b.is_match(&name)) {
+                branches.push((name, reference.peel_to_commit()?.id()));
             }
         }
+        if branches.is_empty() {
+            warn!("not found branches: {}", self.clone_url);
+        }
+        Ok(branches)
+    }
 
-        let mut found_new_version_num = 0;
+    /// all tags, or the tags reachable from the branch with `reachable_only`.
+    /// with `branches`, the tags reachable from each matched branch are collected into its channel.
+    /// new versions have the changes since the previous version of the channel in the order of
+    /// `version_scheme` (or the date), except at the first check which finds all versions new.
+    /// and the signatures of the tags are verified with `gpg_keyring` or `allowed_signers`
+    /// when the version is new or its tag is moved to another object.
+    fn collect_releases(
+        &self,
+        ctx: &CollectorContext,
+        repo: &Repository,
+    ) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let known_versions = ctx.known_versions(&self.project_name);
+        let verified_tags = if self.verifier.is_enabled() {
+            ctx.verified_tags(&self.project_name)
+        } else {
+            HashMap::new()
+        };
 
-        let reader = BufReader::new(s.trim_end().as_bytes());
-        let mut rdr = csv::ReaderBuilder::new()
-            .delimiter(b'\t')
-            .has_headers(false)
-            .from_reader(reader);
-        for row in rdr.deserialize() {
-            match row {
-                Ok(_) => {}
-                Err(e) => {
-                    error!("fail deserialize csv data. error: {:?}", e);
+        let (channels, reachable_only) = if self.branches.is_empty() {
+            let branch_head = repo.head()?.peel_to_commit()?.id();
+            (vec![(self.branch.clone(), branch_head)], self.reachable_only)
+        } else {
+            (self.matched_branches(repo)?, true)
+        };
+
+        let mut releases = vec![];
+        let mut commits = vec![];
+        for reference in repo.references_glob("refs/tags/*")? {
+            let reference = reference?;
+            let name = match reference.shorthand() {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let version = match self.tag_version(&name) {
+                Some(v) => v,
+                None => continue,
+            };
+            let commit = match reference.peel_to_commit() {
+                Ok(commit) => commit,
+                Err(_) => {
+                    debug!("not a commit tag: {}", name);
                     continue;
                 }
             };
-            let mut record: GitInfo = row.unwrap();
-            record.tag = {
-                match &self.version_regex {
-                    Some(vregex) => match vregex.captures(&record.tag) {
-                        Some(caps) => caps.get(1).unwrap().as_str().to_string(),
-                        None => "".to_string(),
-                    },
-                    None => record.tag,
-                }
+            let bump_date = tag_date(repo, &reference)?;
+            let notes = tag_message(repo, &reference);
+            let signature = match reference.target().map(|oid| oid.to_string()) {
+                Some(tag_oid) if self.verifier.is_enabled() => match verified_tags.get(&version) {
+                    Some(Some(verified)) if *verified == tag_oid => None,
+                    Some(Some(verified)) => {
+                        warn!(
+                            "{} {}: tag is moved from {} to {}",
+                            self.project_name, version, verified, tag_oid
+                        );
+                        self.verify(repo, &reference, &version, tag_oid)
+                    }
+                    _ => self.verify(repo, &reference, &version, tag_oid),
+                },
+                _ => None,
             };
-            debug!("record: {:?}", record);
-            if record.tag.is_empty() {
+
+            for (channel, tip) in channels.iter() {
+                if reachable_only && commit.id() != *tip && !repo.graph_descendant_of(*tip, commit.id())? {
+                    debug!("not reachable from {}: {}", channel, name);
+                    continue;
+                }
+                releases.push(CollectedRelease {
+                    channel: channel.clone(),
+                    version: version.clone(),
+                    bump_date,
+                    url: Some(format!("{}/releases/tag/{}", self.url, version)),
+                    yanked: false,
+                    release_notes: notes.clone(),
+                    changes: None,
+                    signature: signature.clone(),
+                });
+                commits.push(commit.id());
+            }
+        }
+
+        // the versions tagged at the same time are stored in the version order,
+        // the last stored one is the latest version of them
+        let mut collected: Vec<(CollectedRelease, Oid)> = releases.into_iter().zip(commits).collect();
+        collected.sort_by(|(a, _), (b, _)| {
+            a.bump_date
+                .cmp(&b.bump_date)
+                .then_with(|| version::compare(None, &a.version, &b.version))
+        });
+        let (mut releases, commits): (Vec<CollectedRelease>, Vec<Oid>) = collected.into_iter().unzip();
+
+        // shallow mirror does not have the history between the versions
+        if repo.is_shallow() || known_versions.is_empty() {
+            return Ok(releases);
+        }
+        let mut order: Vec<usize> = (0..releases.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&releases[a], &releases[b]);
+            a.channel.cmp(&b.channel).then_with(|| match self.version_scheme {
+                // a backport is compared with the previous version of its line, not the newest tag
+                Some(scheme) => version::compare(Some(scheme), &a.version, &b.version),
+                None => Ordering::Equal,
+            })
+        });
+        for pair in order.windows(2) {
+            let (previous, current) = (pair[0], pair[1]);
+            let release = &releases[current];
+            if releases[previous].channel != release.channel
+                || known_versions.contains(&(release.channel.clone(), release.version.clone()))
+            {
                 continue;
             }
+            let log = commits_between(repo, commits[previous], commits[current])?;
+            let stats = diffstat(repo, commits[previous], commits[current])?;
+            releases[current].changes = Some(change_summary(&releases[previous].version, &log, &stats));
+        }
+        Ok(releases)
+    }
 
-            let bump_date =
-                NaiveDateTime::parse_from_str(record.date.as_str(), "%Y/%m/%d %H:%M:%S").expect("fail parse date");
-            let version_history = database::VersionHistory {
-                id: 0,
-                project_name: self.project_name.clone(),
-                channel: self.branch.clone(),
-                version: record.tag.clone(),
-                bump_date,
-                url: Some(format!("{}/releases/tag/{}", self.url, record.tag)),
-            };
+    /// signature status of the tag, anything but a good signature is warned
+    fn verify(&self, repo: &Repository, reference: &Reference, version: &str, tag_oid: String) -> Option<TagSignature> {
+        let status = match self.verifier.verify_tag(repo, reference.target()?) {
+            Ok(status) => status,
+            Err(e) => {
+                error!("{} {}: verify signature error: {}", self.project_name, version, e);
+                return None;
+            }
+        };
+        match &status {
+            SignatureStatus::Good(_) => info!("{} {}: {}", self.project_name, version, status),
+            _ => warn!("{} {}: {}", self.project_name, version, status),
+        }
+        Some(TagSignature { tag_oid, status })
+    }
 
-            match database::insert_version_history(&mut dbconn, &version_history) {
-                Ok(n) => {
-                    if n != 0 {
-                        info!("insert data. {:?}", version_history);
-                        found_new_version_num += 0;
-                    }
-                }
-                Err(e) => error!("insert error: {:?}", e),
+    /// commit of the tag of the version, or of the revision (`HEAD~3`)
+    fn version_commit(&self, repo: &Repository, version: &str) -> Result<Oid, CollectorError> {
+        for reference in repo.references_glob("refs/tags/*")? {
+            let reference = reference?;
+            let name = match reference.shorthand() {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            if self.tag_version(&name).as_deref() == Some(version) {
+                return Ok(reference.peel_to_commit()?.id());
             }
         }
+        Ok(repo.revparse_single(version)?.peel_to_commit()?.id())
+    }
+
+    /// changes and shortlog (author, commit summaries) between two versions in the local repository
+    pub fn diff(&self, from: &str, to: &str) -> Result<(ChangeSummary, Shortlog), CollectorError> {
+        if self.mode == GitMode::Remote {
+            return Err(CollectorError::Config(
+                "git_mode = \"remote\" has no local repository".to_string(),
+            ));
+        }
+        let repo = Repository::open(self.repository_directory()).map_err(|_| {
+            CollectorError::Config(format!(
+                "not found local repository, run check first: {}",
+                self.clone_url
+            ))
+        })?;
+        if repo.is_shallow() {
+            return Err(CollectorError::Config(format!(
+                "shallow repository has no history: {}",
+                self.clone_url
+            )));
+        }
+
+        let from_commit = self.version_commit(&repo, from)?;
+        let to_commit = self.version_commit(&repo, to)?;
+        let log = commits_between(&repo, from_commit, to_commit)?;
+        let stats = diffstat(&repo, from_commit, to_commit)?;
+
+        let mut shortlog: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for commit in log.iter() {
+            shortlog
+                .entry(commit.author.clone())
+                .or_default()
+                .push(commit.summary.clone());
+        }
+        Ok((change_summary(from, &log, &stats), shortlog.into_iter().collect()))
+    }
+}
 
-        if env::set_current_dir(old_curdir).is_err() {
-            error!("change dir error");
+#[async_trait(?Send)]
+impl Collector for GitCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        if self.mode == GitMode::Remote {
+            return self.collect_remote_releases();
         }
+        let repo = self.open_repository()?;
+        self.update(&repo)?;
+        self.collect_releases(ctx, &repo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETRC: &str = "
+machine github.com
+  login alice
+  password gh-token
+
+machine gitlab.example.com login bob
+machine other.example.com login carol password default
+
+machine git.example.com
+  login dave
+  password git-token
+
+default
+  login anonymous
+  password guest
+";
+
+    fn credential(login: &str, password: &str) -> Option<(String, String)> {
+        Some((login.to_string(), password.to_string()))
+    }
+
+    #[test]
+    fn netrc_machine_entries() {
+        assert_eq!(parse_netrc(NETRC, "github.com"), credential("alice", "gh-token"));
+        // the password `default` of the previous entry is not the `default` entry
+        assert_eq!(parse_netrc(NETRC, "git.example.com"), credential("dave", "git-token"));
+        // the entry without password does not take one from the next entries
+        assert_eq!(parse_netrc(NETRC, "gitlab.example.com"), None);
+    }
 
-        found_new_version_num
+    #[test]
+    fn netrc_default_entry() {
+        assert_eq!(
+            parse_netrc(NETRC, "unknown.example.com"),
+            credential("anonymous", "guest")
+        );
+        assert_eq!(
+            parse_netrc("machine github.com login alice password x", "gitlab.com"),
+            None
+        );
     }
 }
@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use std::collections::HashSet;
+
+use crate::collector::{
+    get_all_pages, get_all_pages_if_modified, parse_datetime, release_notes, CollectedRelease, Collector,
+    CollectorContext, CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+#[derive(Debug, Deserialize)]
+struct GiteaRelease {
+    tag_name: String,
+    html_url: String,
+    draft: bool,
+    created_at: String,
+    published_at: Option<String>,
+    body: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaCommit {
+    created: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaTag {
+    name: String,
+    message: Option<String>,
+    commit: GiteaCommit,
+}
+
+/// collector for Gitea compatible API (Gitea, Forgejo, Codeberg)
+pub struct GiteaCollector {
+    project_name: String,
+    base_url: String,
+    owner: String,
+    repo_name: String,
+    access_token: Option<String>,
+}
+
+impl GiteaCollector {
+    pub fn new(project_name: &str, base_url: &str, owner: &str, repo_name: &str, access_token: Option<String>) -> Self {
+        Self {
+            project_name: project_name.to_string(),
+            base_url: base_url.to_string(),
+            owner: owner.to_string(),
+            repo_name: repo_name.to_string(),
+            access_token,
+        }
+    }
+
+    /// `source = { gitea = "codeberg.org/owner/repo" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let gitea_repo = source.gitea.as_deref().unwrap_or_default();
+        let tmp: Vec<&str> = gitea_repo.trim_end_matches('/').split('/').collect();
+        if tmp.len() != 3 || tmp.iter().any(|s| s.is_empty()) {
+            return Err(CollectorError::Config(format!(
+                "invalid gitea repository: {}",
+                gitea_repo
+            )));
+        }
+        let (host, owner, repo) = (tmp[0], tmp[1], tmp[2]);
+        let instance = ctx.config.gitea.get(host).cloned().unwrap_or_default();
+        Ok(Box::new(Self::new(
+            project_name,
+            &instance.base_url(host),
+            owner,
+            repo,
+            source.token.clone().or(instance.token),
+        )))
+    }
+
+    fn api_url(&self, resource: &str) -> String {
+        format!(
+            "{}/api/v1/repos/{}/{}/{}?limit=50",
+            self.base_url, self.owner, self.repo_name, resource
+        )
+    }
+
+    fn headers(&self) -> Result<HeaderMap, CollectorError> {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &self.access_token {
+            let value = HeaderValue::from_str(format!("token {}", token).as_str())
+                .map_err(|e| CollectorError::Config(format!("invalid gitea token: {}", e)))?;
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for GiteaCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let headers = self.headers()?;
+        // every release has its tag, so the tags ordered by creation tell whether anything is new
+        let tags_url = self.api_url("tags");
+        let gitea_tags: Vec<GiteaTag> =
+            match get_all_pages_if_modified(ctx, &self.project_name, &tags_url, &headers).await? {
+                Some(tags) => tags,
+                None => return Ok(vec![]),
+            };
+        debug!("gitea.tag: {:#?}", gitea_tags);
+        let gitea_releases: Vec<GiteaRelease> = get_all_pages(ctx, &self.api_url("releases"), &headers).await?;
+        debug!("gitea.release: {:#?}", gitea_releases);
+
+        let mut releases = vec![];
+        let mut release_tags = HashSet::new();
+        for release in gitea_releases.iter().filter(|r| !r.draft) {
+            let date = release.published_at.as_ref().unwrap_or(&release.created_at);
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version: release.tag_name.clone(),
+                bump_date: parse_datetime(date)?,
+                url: Some(release.html_url.clone()),
+                yanked: false,
+                release_notes: release_notes(release.body.as_deref()),
+                changes: None,
+                signature: None,
+            });
+            release_tags.insert(release.tag_name.as_str());
+        }
+
+        // tags without release
+        for tag in gitea_tags.iter().filter(|t| !release_tags.contains(t.name.as_str())) {
+            let date = match &tag.commit.created {
+                Some(d) => d,
+                None => {
+                    warn!("not found commit date of tag: {}", tag.name);
+                    continue;
+                }
+            };
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version: tag.name.clone(),
+                bump_date: parse_datetime(date)?,
+                url: Some(format!(
+                    "{}/{}/{}/src/tag/{}",
+                    self.base_url, self.owner, self.repo_name, tag.name
+                )),
+                yanked: false,
+                release_notes: release_notes(tag.message.as_deref()),
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+}
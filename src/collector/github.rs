@@ -1,103 +1,713 @@
-use chrono::NaiveDateTime;
-use reqwest::Client;
-use url::Url;
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::time::Duration;
 
-use crate::database;
+use crate::collector::{
+    next_link, parse_datetime, release_notes, CollectedRelease, Collector, CollectorContext, CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
 
 const GITHUB_API: &str = "https://api.github.com";
+const GITHUB_WEB: &str = "https://github.com";
+const GITHUB_ENTERPRISE_API_PATH: &str = "/api/v3";
+const GITHUB_ENTERPRISE_GRAPHQL_PATH: &str = "/api/graphql";
+const PRERELEASE_CHANNEL: &str = "prerelease";
+const DEFAULT_GRAPHQL_BATCH_SIZE: usize = 50;
+/// number of the latest releases and tags of each repository in GraphQL batch query
+const GRAPHQL_NODES: usize = 20;
+/// seconds to sleep at most when the rate limit is exhausted, longer wait defers the project
+const DEFAULT_RATE_LIMIT_MAX_WAIT: u64 = 60;
+/// tags resolved for the commit date in a check, the rest are resolved at the next check
+const MAX_NEW_TAGS: usize = 50;
+/// seconds to wait after the secondary rate limit error without `Retry-After`
+const SECONDARY_RATE_LIMIT_WAIT: u64 = 60;
+/// `X-RateLimit-Resource` of the REST API and the GraphQL API, they have separate budgets
+const CORE_RESOURCE: &str = "core";
+const GRAPHQL_RESOURCE: &str = "graphql";
 
 #[derive(Debug, Serialize, Deserialize)]
 struct GitHubRelease {
     html_url: String,
     tag_name: String,
+    draft: bool,
+    prerelease: bool,
     created_at: String,
+    published_at: Option<String>,
+    body: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubTagCommit {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubTag {
+    name: String,
+    commit: GitHubTagCommit,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubSignature {
+    date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCommitDetail {
+    committer: GitHubSignature,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCommit {
+    commit: GitHubCommitDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLResponse {
+    data: Option<HashMap<String, Option<GraphQLRepository>>>,
+    errors: Option<Vec<GraphQLError>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQLPageInfo {
+    has_next_page: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQLConnection<T> {
+    page_info: GraphQLPageInfo,
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQLRelease {
+    tag_name: String,
+    url: String,
+    is_draft: bool,
+    is_prerelease: bool,
+    created_at: String,
+    published_at: Option<String>,
+    description: Option<String>,
+}
+
+/// Commit, or annotated Tag which points to Commit
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQLTarget {
+    committed_date: Option<String>,
+    /// message of annotated Tag
+    message: Option<String>,
+    target: Option<Box<GraphQLTarget>>,
+}
+
+impl GraphQLTarget {
+    fn committed_date(&self) -> Option<&str> {
+        match &self.committed_date {
+            Some(d) => Some(d.as_str()),
+            None => self.target.as_ref()?.committed_date(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLRef {
+    name: String,
+    target: GraphQLTarget,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLRepository {
+    releases: GraphQLConnection<GraphQLRelease>,
+    refs: Option<GraphQLConnection<GraphQLRef>>,
+}
+
+/// latest releases (or tags) of one repository found by GraphQL batch query
+#[derive(Debug)]
+struct BatchedRepository {
+    releases: Vec<CollectedRelease>,
+    has_next_page: bool,
+}
+
+/// `X-RateLimit-Remaining` and `X-RateLimit-Reset` (unix time) of the last response of the resource
+#[derive(Debug, Clone, Copy)]
+struct RateLimit {
+    remaining: u64,
+    reset: i64,
+}
+
+/// (api url, token, resource)
+type Endpoint = (String, Option<String>, String);
+
+/// state shared by the github collectors of one `check` run,
+/// results of GraphQL batch queries and rate limit of each endpoint (api url, token and resource)
+#[derive(Default)]
+pub struct GitHubState {
+    fetched_endpoints: RefCell<HashSet<(String, String)>>,
+    repositories: RefCell<HashMap<String, BatchedRepository>>,
+    rate_limits: RefCell<HashMap<Endpoint, RateLimit>>,
 }
 
 pub struct GitHubCollector {
-    db_url: String,
     project_name: String,
-    client: Client,
+    api_url: String,
     owner: String,
     repo_name: String,
     access_token: Option<String>,
 }
 
+/// web url of the API url (`https://github.example.com/api/v3` -> `https://github.example.com`)
+fn web_url(api_url: &str) -> String {
+    if api_url == GITHUB_API {
+        GITHUB_WEB.to_string()
+    } else {
+        api_url.trim_end_matches(GITHUB_ENTERPRISE_API_PATH).to_string()
+    }
+}
+
+/// GraphQL endpoint of the API url (`https://github.example.com/api/v3` -> `https://github.example.com/api/graphql`)
+fn graphql_url(api_url: &str) -> String {
+    match api_url.strip_suffix(GITHUB_ENTERPRISE_API_PATH) {
+        Some(base) => format!("{}{}", base, GITHUB_ENTERPRISE_GRAPHQL_PATH),
+        None => format!("{}/graphql", api_url),
+    }
+}
+
+/// API url and access token of the source, `api_url` and `token` of the source override
+/// the global `github_api_url` and `github_access_token` (or `GITHUB_TOKEN` environment variable)
+fn resolve_endpoint(ctx: &CollectorContext, source: &ProjectSourceConfig) -> (String, Option<String>) {
+    let api_url = source
+        .api_url
+        .as_deref()
+        .or(ctx.config.github_api_url.as_deref())
+        .unwrap_or(GITHUB_API);
+    let access_token = source
+        .token
+        .clone()
+        .or_else(|| ctx.config.github_access_token.clone())
+        .or_else(|| env::var("GITHUB_TOKEN").ok());
+    (api_url.trim_end_matches('/').to_string(), access_token)
+}
+
+fn batch_key(api_url: &str, owner: &str, repo_name: &str) -> String {
+    format!("{}/{}/{}", api_url, owner, repo_name).to_lowercase()
+}
+
+fn release_of(
+    tag_name: &str,
+    html_url: &str,
+    prerelease: bool,
+    date: &str,
+    notes: Option<&str>,
+) -> Result<CollectedRelease, CollectorError> {
+    let channel = if prerelease { PRERELEASE_CHANNEL } else { "" };
+    Ok(CollectedRelease {
+        channel: channel.to_string(),
+        version: tag_name.to_string(),
+        bump_date: parse_datetime(date)?,
+        url: Some(html_url.to_string()),
+        yanked: false,
+        release_notes: release_notes(notes),
+        changes: None,
+        signature: None,
+    })
+}
+
+fn tag_of(
+    api_url: &str,
+    owner: &str,
+    repo_name: &str,
+    name: &str,
+    date: &str,
+    message: Option<&str>,
+) -> Result<CollectedRelease, CollectorError> {
+    Ok(CollectedRelease {
+        channel: "".to_string(),
+        version: name.to_string(),
+        bump_date: parse_datetime(date)?,
+        url: Some(format!(
+            "{}/{}/{}/releases/tag/{}",
+            web_url(api_url),
+            owner,
+            repo_name,
+            name
+        )),
+        yanked: false,
+        release_notes: release_notes(message),
+        changes: None,
+        signature: None,
+    })
+}
+
+/// releases of the GraphQL result, or tags when the repository has no release
+fn batched_repository(
+    api_url: &str,
+    owner: &str,
+    repo_name: &str,
+    repository: &GraphQLRepository,
+) -> Result<BatchedRepository, CollectorError> {
+    let mut releases = vec![];
+    if !repository.releases.nodes.is_empty() {
+        for release in repository.releases.nodes.iter().filter(|r| !r.is_draft) {
+            let date = release.published_at.as_ref().unwrap_or(&release.created_at);
+            releases.push(release_of(
+                &release.tag_name,
+                &release.url,
+                release.is_prerelease,
+                date,
+                release.description.as_deref(),
+            )?);
+        }
+        return Ok(BatchedRepository {
+            releases,
+            has_next_page: repository.releases.page_info.has_next_page,
+        });
+    }
+
+    let refs = match &repository.refs {
+        Some(refs) => refs,
+        None => {
+            return Ok(BatchedRepository {
+                releases,
+                has_next_page: false,
+            })
+        }
+    };
+    for tag in refs.nodes.iter() {
+        match tag.target.committed_date() {
+            Some(date) => releases.push(tag_of(
+                api_url,
+                owner,
+                repo_name,
+                &tag.name,
+                date,
+                tag.target.message.as_deref(),
+            )?),
+            None => warn!("not found commit date of tag: {}/{} {}", owner, repo_name, tag.name),
+        }
+    }
+    Ok(BatchedRepository {
+        releases,
+        has_next_page: refs.page_info.has_next_page,
+    })
+}
+
+/// aliased query (`r0: repository(...) {...} r1: ...`) of the repositories
+fn batch_query(repositories: &[(String, String)]) -> String {
+    let mut query = String::from("query {\n");
+    for (i, (owner, repo_name)) in repositories.iter().enumerate() {
+        query.push_str(&format!(
+            "  r{i}: repository(owner: {owner}, name: {name}) {{
+    releases(first: {n}, orderBy: {{field: CREATED_AT, direction: DESC}}) {{
+      pageInfo {{ hasNextPage }}
+      nodes {{ tagName url isDraft isPrerelease createdAt publishedAt description }}
+    }}
+    refs(refPrefix: \"refs/tags/\", first: {n}, orderBy: {{field: TAG_COMMIT_DATE, direction: DESC}}) {{
+      pageInfo {{ hasNextPage }}
+      nodes {{ name target {{ ... on Commit {{ committedDate }} ... on Tag {{ message target {{ ... on Commit {{ committedDate }} }} }} }} }}
+    }}
+  }}
+",
+            i = i,
+            owner = serde_json::Value::from(owner.as_str()),
+            name = serde_json::Value::from(repo_name.as_str()),
+            n = GRAPHQL_NODES,
+        ));
+    }
+    query.push('}');
+    query
+}
+
 impl GitHubCollector {
-    pub fn new(db_url: &str, project_name: &str, owner: &str, repo_name: &str, access_token: Option<String>) -> Self {
+    pub fn new(project_name: &str, api_url: &str, owner: &str, repo_name: &str, access_token: Option<String>) -> Self {
         Self {
-            db_url: db_url.to_string(),
             project_name: project_name.to_string(),
-            client: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
             owner: owner.to_string(),
             repo_name: repo_name.to_string(),
             access_token,
         }
     }
 
-    fn insert(&self, tag: &str, date: &str, release_url: &str) -> usize {
-        let mut dbconn = database::get_database_connection(self.db_url.as_str());
-        let bump_date = NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%SZ").expect("fail parse date");
-        let version_history = database::VersionHistory {
-            id: 0,
-            project_name: self.project_name.clone(),
-            channel: "".to_string(),
-            version: tag.to_string(),
-            bump_date,
-            url: Some(release_url.to_string()),
+    /// `source = { github = "owner/repo" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let github_repo = source.github.as_deref().unwrap_or_default();
+        let (api_url, access_token) = resolve_endpoint(ctx, source);
+        match github_repo.split_once('/') {
+            Some((owner, repo)) => Ok(Box::new(Self::new(project_name, &api_url, owner, repo, access_token))),
+            None => Err(CollectorError::Config(format!(
+                "invalid github repository: {}",
+                github_repo
+            ))),
+        }
+    }
+
+    fn repo_api_url(&self, resource: &str) -> String {
+        format!(
+            "{}/repos/{}/{}/{}?per_page=100",
+            self.api_url, self.owner, self.repo_name, resource
+        )
+    }
+
+    fn headers(&self) -> Result<HeaderMap, CollectorError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("application/vnd.github+json"));
+        if let Some(token) = &self.access_token {
+            let value = HeaderValue::from_str(format!("Bearer {}", token).as_str())
+                .map_err(|e| CollectorError::Config(format!("invalid github token: {}", e)))?;
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+
+    fn endpoint(&self, resource: &str) -> Endpoint {
+        (self.api_url.clone(), self.access_token.clone(), resource.to_string())
+    }
+
+    /// sleep `secs` seconds, or defer the project when it is longer than `github_rate_limit_max_wait`
+    async fn wait(&self, ctx: &CollectorContext<'_>, secs: u64) -> Result<(), CollectorError> {
+        let max_wait = ctx
+            .config
+            .github_rate_limit_max_wait
+            .unwrap_or(DEFAULT_RATE_LIMIT_MAX_WAIT);
+        if secs > max_wait {
+            return Err(CollectorError::RateLimited(format!(
+                "github rate limit of {} is exhausted, reset after {}s",
+                self.api_url, secs
+            )));
+        }
+        info!("wait {}s for github rate limit", secs);
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+        Ok(())
+    }
+
+    /// wait until the reset time when no request is left in the budget of this endpoint
+    async fn wait_for_budget(&self, ctx: &CollectorContext<'_>, resource: &str) -> Result<(), CollectorError> {
+        let rate_limit = ctx.github.rate_limits.borrow().get(&self.endpoint(resource)).copied();
+        if let Some(rate_limit) = rate_limit.filter(|r| r.remaining == 0) {
+            let secs = rate_limit.reset - Utc::now().timestamp();
+            if secs > 0 {
+                self.wait(ctx, secs as u64 + 1).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn update_rate_limit(&self, ctx: &CollectorContext<'_>, resource: &str, headers: &HeaderMap) -> Option<RateLimit> {
+        let header = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+        let rate_limit = RateLimit {
+            remaining: header("x-ratelimit-remaining")? as u64,
+            reset: header("x-ratelimit-reset")?,
         };
+        let resource = headers
+            .get("x-ratelimit-resource")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or(resource);
+        debug!("github rate limit of {}: {:?}", resource, rate_limit);
+        ctx.github
+            .rate_limits
+            .borrow_mut()
+            .insert(self.endpoint(resource), rate_limit);
+        Some(rate_limit)
+    }
+
+    /// send request within the rate limit of the resource (`core` or `graphql`),
+    /// retry once after primary or secondary rate limit error
+    async fn send(
+        &self,
+        ctx: &CollectorContext<'_>,
+        req: RequestBuilder,
+        resource: &str,
+    ) -> Result<Response, CollectorError> {
+        for attempt in 0..2 {
+            self.wait_for_budget(ctx, resource).await?;
+            let res = req
+                .try_clone()
+                .ok_or_else(|| CollectorError::Config("github request can not be retried".to_string()))?
+                .send()
+                .await?;
+            let rate_limit = self.update_rate_limit(ctx, resource, res.headers());
 
-        match database::insert_version_history(&mut dbconn, &version_history) {
-            Ok(n) => {
-                if n != 0 {
-                    info!("insert data. {:?}", version_history);
+            let status = res.status();
+            if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(res.error_for_status()?);
+            }
+            let retry_after = res
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok());
+            let secs = match retry_after {
+                // secondary rate limit
+                Some(secs) => secs,
+                // primary rate limit, wait_for_budget waits until the reset
+                None if matches!(rate_limit, Some(r) if r.remaining == 0) => 0,
+                // secondary rate limit without `Retry-After`, or the other forbidden error
+                None => {
+                    let error = res.error_for_status_ref().err();
+                    let message = res.text().await?;
+                    match error {
+                        Some(e) if status == StatusCode::FORBIDDEN && !message.contains("secondary rate limit") => {
+                            return Err(e.into())
+                        }
+                        _ => SECONDARY_RATE_LIMIT_WAIT,
+                    }
                 }
-                n
+            };
+            if attempt == 0 && secs > 0 {
+                self.wait(ctx, secs).await?;
+            }
+        }
+        Err(CollectorError::RateLimited(format!(
+            "github rate limit of {} is exceeded",
+            self.api_url
+        )))
+    }
+
+    /// GET json array through all pages, the first page is a conditional request.
+    /// returns `None` when it is not modified, which means nothing is new only for the list
+    /// ordered `newest_first`, so validators of the other multi-page lists are not kept.
+    async fn get_pages<T: DeserializeOwned>(
+        &self,
+        ctx: &CollectorContext<'_>,
+        url: &str,
+        newest_first: bool,
+    ) -> Result<Option<Vec<T>>, CollectorError> {
+        let headers = self.headers()?;
+        let mut items = vec![];
+        let mut next_url = Some(url.to_string());
+        while let Some(page_url) = next_url {
+            debug!("GET {}", page_url);
+            let mut req = ctx.client.get(page_url.as_str()).headers(headers.clone());
+            let first_page = page_url == url;
+            if first_page {
+                req = ctx.conditional(&self.project_name, url, req);
+            }
+            let res = self.send(ctx, req, CORE_RESOURCE).await?;
+            if first_page && ctx.not_modified(&self.project_name, url, &res) {
+                return Ok(None);
             }
-            Err(e) => {
-                error!("insert error: {:?}", e);
-                0
+            next_url = next_link(res.headers());
+            if first_page && next_url.is_some() && !newest_first {
+                ctx.forget_validators(&self.project_name, url);
             }
+            let mut page: Vec<T> = res.json().await?;
+            items.append(&mut page);
         }
+        Ok(Some(items))
     }
 
-    pub async fn get_releases(self) -> Result<usize, reqwest::Error> {
+    async fn get_releases(&self, ctx: &CollectorContext<'_>) -> Result<Option<Vec<GitHubRelease>>, CollectorError> {
         debug!("get_releases");
-        let url = Url::parse(GITHUB_API).unwrap();
-        let url_path = format!("repos/{}/{}/releases", self.owner, self.repo_name);
-        let mut get_url = url.join(url_path.as_str()).unwrap();
-        let res: Vec<GitHubRelease> = match &self.access_token {
-            Some(token) => {
-                let t = format!("access_token={}", token);
-                get_url.set_query(Some(t.as_str()));
-                self.client
-                    .get(get_url.as_str())
-                    .header("user-agent", "tamatebako-client")
-                    .send()
-                    .await?
-                    .json()
-                    .await?
+        self.get_pages(ctx, &self.repo_api_url("releases"), true).await
+    }
+
+    /// tags with the commit date, for repositories which never publish releases.
+    /// each new tag costs a request, the tags resolved before the rate limit are kept
+    /// and the rest are resolved at the next check.
+    async fn get_tags(&self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        debug!("get_tags");
+        let headers = self.headers()?;
+        let tags_url = self.repo_api_url("tags");
+        // tags are ordered by name, not by date
+        let tags: Vec<GitHubTag> = match self.get_pages(ctx, &tags_url, false).await? {
+            Some(tags) => tags,
+            None => return Ok(vec![]),
+        };
+        debug!("github.tag: {:#?}", tags);
+
+        let known_versions = ctx.known_versions(&self.project_name);
+        let mut releases = vec![];
+        let mut pending = false;
+        for tag in tags.iter() {
+            if known_versions.contains(&("".to_string(), tag.name.clone())) {
+                continue;
             }
-            None => {
-                self.client
-                    .get(get_url.as_str())
-                    .header("user-agent", "tamatebako-client")
-                    .send()
-                    .await?
-                    .json()
-                    .await?
+            if releases.len() >= MAX_NEW_TAGS {
+                info!("{}: resolve the rest of new tags at the next check", self.project_name);
+                pending = true;
+                break;
             }
-        };
+            debug!("GET {}", tag.commit.url);
+            let req = ctx.client.get(tag.commit.url.as_str()).headers(headers.clone());
+            let commit: GitHubCommit = match self.send(ctx, req, CORE_RESOURCE).await {
+                Ok(res) => res.json().await?,
+                Err(CollectorError::RateLimited(e)) if !releases.is_empty() => {
+                    warn!(
+                        "{}: {}, resolve the rest of new tags at the next check",
+                        self.project_name, e
+                    );
+                    pending = true;
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            releases.push(tag_of(
+                &self.api_url,
+                &self.owner,
+                &self.repo_name,
+                &tag.name,
+                &commit.commit.committer.date,
+                None,
+            )?);
+        }
+        if pending {
+            ctx.forget_validators(&self.project_name, &tags_url);
+        }
+        Ok(releases)
+    }
+
+    /// query the latest releases and tags of all github projects with the same endpoint
+    /// as this collector, `batch_size` repositories per request
+    async fn fetch_batch(&self, ctx: &CollectorContext<'_>, batch_size: usize) -> Result<(), CollectorError> {
+        let mut repositories = vec![];
+        let mut keys = HashSet::new();
+        for project in ctx.config.projects.values() {
+            let source = match &project.source {
+                Some(source) => source,
+                None => continue,
+            };
+            let github_repo = match &source.github {
+                Some(github_repo) => github_repo,
+                None => continue,
+            };
+            if resolve_endpoint(ctx, source) != (self.api_url.clone(), self.access_token.clone()) {
+                continue;
+            }
+            if let Some((owner, repo_name)) = github_repo.split_once('/') {
+                if keys.insert(batch_key(&self.api_url, owner, repo_name)) {
+                    repositories.push((owner.to_string(), repo_name.to_string()));
+                }
+            }
+        }
 
+        let url = graphql_url(&self.api_url);
+        let headers = self.headers()?;
+        for chunk in repositories.chunks(batch_size) {
+            debug!("POST {} ({} repositories)", url, chunk.len());
+            let body = serde_json::json!({ "query": batch_query(chunk) });
+            let res: GraphQLResponse = self
+                .send(
+                    ctx,
+                    ctx.client.post(url.as_str()).headers(headers.clone()).json(&body),
+                    GRAPHQL_RESOURCE,
+                )
+                .await?
+                .json()
+                .await?;
+            for error in res.errors.unwrap_or_default() {
+                warn!("github graphql error: {}", error.message);
+            }
+
+            let data = res.data.unwrap_or_default();
+            for (i, (owner, repo_name)) in chunk.iter().enumerate() {
+                let repository = match data.get(&format!("r{}", i)) {
+                    Some(Some(repository)) => repository,
+                    _ => continue,
+                };
+                match batched_repository(&self.api_url, owner, repo_name, repository) {
+                    Ok(batched) => {
+                        ctx.github
+                            .repositories
+                            .borrow_mut()
+                            .insert(batch_key(&self.api_url, owner, repo_name), batched);
+                    }
+                    Err(e) => warn!("github graphql result of {}/{}: {}", owner, repo_name, e),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// releases found by GraphQL batch query, `None` falls back to REST API
+    async fn get_batched(&self, ctx: &CollectorContext<'_>) -> Option<Vec<CollectedRelease>> {
+        let batch_size = ctx
+            .config
+            .github_graphql_batch_size
+            .unwrap_or(DEFAULT_GRAPHQL_BATCH_SIZE);
+        // GraphQL API requires authentication
+        let access_token = self.access_token.as_ref()?;
+        if batch_size == 0 {
+            return None;
+        }
+
+        let endpoint = (self.api_url.clone(), access_token.clone());
+        let fetched = ctx.github.fetched_endpoints.borrow().contains(&endpoint);
+        if !fetched {
+            ctx.github.fetched_endpoints.borrow_mut().insert(endpoint);
+            if let Err(e) = self.fetch_batch(ctx, batch_size).await {
+                warn!("github graphql batch error: {}", e);
+            }
+        }
+
+        let key = batch_key(&self.api_url, &self.owner, &self.repo_name);
+        let batched = ctx.github.repositories.borrow_mut().remove(&key)?;
+        debug!("github.batched: {:#?}", batched);
+
+        // versions older than the batch window are missed unless some of the batch are already stored
+        if batched.has_next_page {
+            let known_versions = ctx.known_versions(&self.project_name);
+            if !batched
+                .releases
+                .iter()
+                .any(|r| known_versions.contains(&(r.channel.clone(), r.version.clone())))
+            {
+                debug!("use REST API for full history: {}/{}", self.owner, self.repo_name);
+                return None;
+            }
+        }
+        Some(batched.releases)
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for GitHubCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        if let Some(releases) = self.get_batched(ctx).await {
+            return Ok(releases);
+        }
+
+        let res = match self.get_releases(ctx).await? {
+            Some(res) => res,
+            None => return Ok(vec![]),
+        };
         debug!("github.release: {:#?}", res);
-        let mut all_insert_num = 0;
-        for release in res.iter() {
-            let release_url = release.html_url.as_str();
-            let tag_name = release.tag_name.as_str();
-            let bump_date = release.created_at.as_str();
 
-            let insert_num = self.insert(tag_name, bump_date, release_url);
-            all_insert_num += insert_num;
+        if res.is_empty() {
+            // unchanged empty releases does not tell whether a new tag exists
+            ctx.forget_validators(&self.project_name, &self.repo_api_url("releases"));
+            return self.get_tags(ctx).await;
+        }
+
+        let mut releases = vec![];
+        for release in res.iter().filter(|r| !r.draft) {
+            let date = release.published_at.as_ref().unwrap_or(&release.created_at);
+            releases.push(release_of(
+                &release.tag_name,
+                &release.html_url,
+                release.prerelease,
+                date,
+                release.body.as_deref(),
+            )?);
         }
-        Ok(all_insert_num)
+        Ok(releases)
     }
 }
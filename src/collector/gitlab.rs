@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::collections::HashSet;
+
+use crate::collector::{
+    get_all_pages, get_all_pages_if_modified, parse_datetime, release_notes, CollectedRelease, Collector,
+    CollectorContext, CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+#[derive(Debug, Deserialize)]
+struct GitLabRelease {
+    tag_name: String,
+    created_at: String,
+    released_at: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabCommit {
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabTag {
+    name: String,
+    message: Option<String>,
+    commit: GitLabCommit,
+}
+
+pub struct GitLabCollector {
+    project_name: String,
+    base_url: String,
+    project_path: String,
+    private_token: Option<String>,
+}
+
+impl GitLabCollector {
+    pub fn new(project_name: &str, base_url: &str, project_path: &str, private_token: Option<String>) -> Self {
+        Self {
+            project_name: project_name.to_string(),
+            base_url: base_url.to_string(),
+            project_path: project_path.to_string(),
+            private_token,
+        }
+    }
+
+    /// `source = { gitlab = "gitlab.com/group/project" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let gitlab_repo = source.gitlab.as_deref().unwrap_or_default();
+        match gitlab_repo.split_once('/') {
+            Some((host, path)) if !path.is_empty() => {
+                let instance = ctx.config.gitlab.get(host).cloned().unwrap_or_default();
+                Ok(Box::new(Self::new(
+                    project_name,
+                    &instance.base_url(host),
+                    path.trim_end_matches('/'),
+                    source.token.clone().or(instance.token),
+                )))
+            }
+            _ => Err(CollectorError::Config(format!(
+                "invalid gitlab project: {}",
+                gitlab_repo
+            ))),
+        }
+    }
+
+    fn api_url(&self, resource: &str) -> String {
+        format!(
+            "{}/api/v4/projects/{}/{}?per_page=100",
+            self.base_url,
+            url::form_urlencoded::byte_serialize(self.project_path.as_bytes()).collect::<String>(),
+            resource
+        )
+    }
+
+    fn headers(&self) -> Result<HeaderMap, CollectorError> {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &self.private_token {
+            let value = HeaderValue::from_str(token)
+                .map_err(|e| CollectorError::Config(format!("invalid gitlab private token: {}", e)))?;
+            headers.insert("PRIVATE-TOKEN", value);
+        }
+        Ok(headers)
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for GitLabCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let headers = self.headers()?;
+        // every release has its tag, so the tags ordered by update tell whether anything is new
+        let tags_url = self.api_url("repository/tags");
+        let gitlab_tags: Vec<GitLabTag> =
+            match get_all_pages_if_modified(ctx, &self.project_name, &tags_url, &headers).await? {
+                Some(tags) => tags,
+                None => return Ok(vec![]),
+            };
+        debug!("gitlab.tag: {:#?}", gitlab_tags);
+        let gitlab_releases: Vec<GitLabRelease> = get_all_pages(ctx, &self.api_url("releases"), &headers).await?;
+        debug!("gitlab.release: {:#?}", gitlab_releases);
+
+        let mut releases = vec![];
+        let mut release_tags = HashSet::new();
+        for release in gitlab_releases.iter() {
+            let date = release.released_at.as_ref().unwrap_or(&release.created_at);
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version: release.tag_name.clone(),
+                bump_date: parse_datetime(date)?,
+                url: Some(format!(
+                    "{}/{}/-/releases/{}",
+                    self.base_url, self.project_path, release.tag_name
+                )),
+                yanked: false,
+                release_notes: release_notes(release.description.as_deref()),
+                changes: None,
+                signature: None,
+            });
+            release_tags.insert(release.tag_name.as_str());
+        }
+
+        // tags without release
+        for tag in gitlab_tags.iter().filter(|t| !release_tags.contains(t.name.as_str())) {
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version: tag.name.clone(),
+                bump_date: parse_datetime(&tag.commit.created_at)?,
+                url: Some(format!("{}/{}/-/tags/{}", self.base_url, self.project_path, tag.name)),
+                yanked: false,
+                release_notes: release_notes(tag.message.as_deref()),
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+}
@@ -0,0 +1,231 @@
+use async_trait::async_trait;
+use regex::Regex;
+use reqwest::StatusCode;
+
+use crate::collector::{parse_datetime, CollectedRelease, Collector, CollectorContext, CollectorError};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+const GO_PROXY: &str = "https://proxy.golang.org";
+const GO_PKG_SITE: &str = "https://pkg.go.dev";
+/// major versions probed after the first one at most
+const MAX_MAJOR_PROBES: u32 = 100;
+
+lazy_static! {
+    static ref RE_MAJOR_SUFFIX: Regex = Regex::new(r"^(.+)/v([0-9]+)$").unwrap();
+}
+
+/// `/@v/<version>.info` and `/@latest`
+#[derive(Debug, Deserialize)]
+struct GoVersionInfo {
+    #[serde(rename = "Version")]
+    version: String,
+    #[serde(rename = "Time")]
+    time: String,
+}
+
+/// response of `/@v/list`
+enum VersionList {
+    Found(String),
+    NotModified,
+    NotFound,
+}
+
+/// collector for GOPROXY protocol servers (proxy.golang.org, Athens, ...)
+///
+/// versions of `<module>/vN` (N >= 2) are collected into the channel `vN`,
+/// and versions of the base module path (including `+incompatible` ones) into the channel "".
+pub struct GoModCollector {
+    proxy_url: String,
+    project_name: String,
+    base_path: String,
+    start_major: u32,
+}
+
+/// case-encoded path (`github.com/Azure` -> `github.com/!azure`)
+fn escape_path(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            escaped.push('!');
+            escaped.push(c.to_ascii_lowercase());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+impl GoModCollector {
+    pub fn new(proxy_url: &str, project_name: &str, module_path: &str) -> Self {
+        let (base_path, start_major) = match RE_MAJOR_SUFFIX.captures(module_path) {
+            Some(caps) => match caps[2].parse::<u32>() {
+                Ok(major) if major >= 2 => (caps[1].to_string(), major),
+                _ => (module_path.to_string(), 1),
+            },
+            None => (module_path.to_string(), 1),
+        };
+        Self {
+            proxy_url: proxy_url.to_string(),
+            project_name: project_name.to_string(),
+            base_path,
+            start_major,
+        }
+    }
+
+    /// `source = { gomod = "github.com/redis/go-redis" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let module_path = source.gomod.as_deref().unwrap_or_default().trim_end_matches('/');
+        if module_path.is_empty() {
+            return Err(CollectorError::Config("empty go module path".to_string()));
+        }
+        let proxy_url = ctx.config.registry_url(source, GO_PROXY);
+        Ok(Box::new(Self::new(&proxy_url, project_name, module_path)))
+    }
+
+    fn module_path(&self, major: u32) -> String {
+        if major < 2 {
+            self.base_path.clone()
+        } else {
+            format!("{}/v{}", self.base_path, major)
+        }
+    }
+
+    /// GET the proxy endpoint, `None` when the module (or version) does not exist
+    async fn get(
+        &self,
+        ctx: &CollectorContext<'_>,
+        module_path: &str,
+        endpoint: &str,
+    ) -> Result<Option<String>, CollectorError> {
+        let url = format!("{}/{}/{}", self.proxy_url, escape_path(module_path), endpoint);
+        debug!("GET {}", url);
+        let res = ctx.client.get(url).send().await?;
+        match res.status() {
+            StatusCode::NOT_FOUND | StatusCode::GONE => Ok(None),
+            _ => Ok(Some(res.error_for_status()?.text().await?)),
+        }
+    }
+
+    /// conditional GET of `@v/list`, the list of each major version is cached separately
+    async fn get_list(&self, ctx: &CollectorContext<'_>, module_path: &str) -> Result<VersionList, CollectorError> {
+        let url = format!("{}/{}/@v/list", self.proxy_url, escape_path(module_path));
+        debug!("GET {}", url);
+        let req = ctx.conditional(&self.project_name, &url, ctx.client.get(url.as_str()));
+        let res = req.send().await?;
+        if ctx.not_modified(&self.project_name, &url, &res) {
+            return Ok(VersionList::NotModified);
+        }
+        match res.status() {
+            StatusCode::NOT_FOUND | StatusCode::GONE => Ok(VersionList::NotFound),
+            _ => {
+                let list = res.error_for_status()?.text().await?;
+                if list.trim().is_empty() {
+                    // `@latest` pseudo-version changes while the list stays empty
+                    ctx.forget_validators(&self.project_name, &url);
+                }
+                Ok(VersionList::Found(list))
+            }
+        }
+    }
+
+    async fn get_info(
+        &self,
+        ctx: &CollectorContext<'_>,
+        module_path: &str,
+        endpoint: &str,
+    ) -> Result<Option<GoVersionInfo>, CollectorError> {
+        match self.get(ctx, module_path, endpoint).await? {
+            Some(body) => serde_json::from_str(&body)
+                .map(Some)
+                .map_err(|e| CollectorError::Parse(format!("{} {}: {}", module_path, endpoint, e))),
+            None => Ok(None),
+        }
+    }
+
+    fn release(
+        &self,
+        module_path: &str,
+        channel: &str,
+        info: &GoVersionInfo,
+    ) -> Result<CollectedRelease, CollectorError> {
+        let url = if self.proxy_url == GO_PROXY {
+            Some(format!("{}/{}@{}", GO_PKG_SITE, module_path, info.version))
+        } else {
+            None
+        };
+        Ok(CollectedRelease {
+            channel: channel.to_string(),
+            version: info.version.clone(),
+            bump_date: parse_datetime(&info.time)?,
+            url,
+            yanked: false,
+            release_notes: None,
+            changes: None,
+            signature: None,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for GoModCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let known_versions = ctx.known_versions(&self.project_name);
+        let mut releases = vec![];
+
+        let mut major = self.start_major;
+        loop {
+            if major - self.start_major > MAX_MAJOR_PROBES {
+                warn!("too many major versions: {}", self.base_path);
+                break;
+            }
+            let module_path = self.module_path(major);
+            let channel = if major < 2 {
+                "".to_string()
+            } else {
+                format!("v{}", major)
+            };
+
+            let list = match self.get_list(ctx, &module_path).await? {
+                VersionList::Found(list) => list,
+                VersionList::NotModified => {
+                    major += 1;
+                    continue;
+                }
+                VersionList::NotFound if major == self.start_major => {
+                    return Err(CollectorError::Config(format!("not found go module: {}", module_path)))
+                }
+                // no more major version
+                VersionList::NotFound => break,
+            };
+            let versions: Vec<&str> = list.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+            debug!("gomod.list: {} {:?}", module_path, versions);
+
+            if versions.is_empty() {
+                // only pseudo-versions, @latest resolves the latest one
+                match self.get_info(ctx, &module_path, "@latest").await? {
+                    Some(info) => releases.push(self.release(&module_path, &channel, &info)?),
+                    // some proxies answer an empty list for any module path, no more major version
+                    None => break,
+                }
+            }
+            for version in versions {
+                if known_versions.contains(&(channel.clone(), version.to_string())) {
+                    continue;
+                }
+                let endpoint = format!("@v/{}.info", escape_path(version));
+                match self.get_info(ctx, &module_path, &endpoint).await? {
+                    Some(info) => releases.push(self.release(&module_path, &channel, &info)?),
+                    None => warn!("not found version info: {}@{}", module_path, version),
+                }
+            }
+
+            major += 1;
+        }
+        Ok(releases)
+    }
+}
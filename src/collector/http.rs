@@ -0,0 +1,241 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use scraper::{Html, Selector};
+use serde_json_path::JsonPath;
+use url::Url;
+
+use crate::collector::{
+    compile_version_regex, get_if_modified, match_version, CollectedRelease, Collector, CollectorContext,
+    CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+const DATE_FORMATS: [&str; 5] = ["%Y-%m-%d", "%Y/%m/%d", "%B %d, %Y", "%b %d, %Y", "%d %B %Y"];
+const DATETIME_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S", "%Y/%m/%d %H:%M:%S"];
+
+/// how to extract versions from the response body
+enum Extractor {
+    /// `jsonpath` (and `date_jsonpath`) for JSON endpoint
+    Json { version: JsonPath, date: Option<JsonPath> },
+    /// `selector` (and `date_selector`) for HTML page
+    Html { version: Selector, date: Option<Selector> },
+    /// `version_regex` matches of the whole body
+    Regex,
+}
+
+/// version text found in the page with optional date text and link
+#[derive(Debug)]
+struct Found {
+    text: String,
+    date: Option<String>,
+    link: Option<String>,
+}
+
+/// parse date text of JSON API or download page, unix time and some common formats are accepted
+fn parse_loose_date(date: &str) -> Option<NaiveDateTime> {
+    let date = date.trim();
+    if let Ok(timestamp) = date.parse::<i64>() {
+        return DateTime::from_timestamp(timestamp, 0).map(|d| d.naive_utc());
+    }
+    if let Ok(d) = DateTime::parse_from_rfc3339(date).or_else(|_| DateTime::parse_from_rfc2822(date)) {
+        return Some(d.naive_utc());
+    }
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(date, f).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|f| NaiveDate::parse_from_str(date, f).ok())
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+fn json_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_json_path(path: &Option<String>, key: &str) -> Result<Option<JsonPath>, CollectorError> {
+    match path {
+        Some(p) => JsonPath::parse(p)
+            .map(Some)
+            .map_err(|e| CollectorError::Config(format!("invalid {}: {}", key, e))),
+        None => Ok(None),
+    }
+}
+
+fn parse_selector(selector: &Option<String>, key: &str) -> Result<Option<Selector>, CollectorError> {
+    match selector {
+        Some(s) => Selector::parse(s)
+            .map(Some)
+            .map_err(|e| CollectorError::Config(format!("invalid {}: {}", key, e))),
+        None => Ok(None),
+    }
+}
+
+/// collector for JSON endpoints and HTML download pages without dedicated API.
+///
+/// `date_jsonpath` and `date_selector` results are paired with the versions by position.
+pub struct HttpCollector {
+    project_name: String,
+    url: String,
+    extractor: Extractor,
+    version_regex: Option<Regex>,
+}
+
+impl HttpCollector {
+    /// `source = { http = "https://example.com/download", selector = "a.download" }`
+    pub fn factory(
+        _ctx: &CollectorContext,
+        project_name: &str,
+        project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let url = source.http.as_deref().unwrap_or_default();
+        if url.is_empty() {
+            return Err(CollectorError::Config("empty http url".to_string()));
+        }
+        let version_regex = compile_version_regex(project)?;
+
+        let extractor = match (
+            parse_json_path(&source.jsonpath, "jsonpath")?,
+            parse_selector(&source.selector, "selector")?,
+        ) {
+            (Some(_), Some(_)) => {
+                return Err(CollectorError::Config(
+                    "jsonpath and selector can not be used together".to_string(),
+                ))
+            }
+            (Some(version), None) => Extractor::Json {
+                version,
+                date: parse_json_path(&source.date_jsonpath, "date_jsonpath")?,
+            },
+            (None, Some(version)) => Extractor::Html {
+                version,
+                date: parse_selector(&source.date_selector, "date_selector")?,
+            },
+            (None, None) if version_regex.is_some() => Extractor::Regex,
+            (None, None) => {
+                return Err(CollectorError::Config(
+                    "http source requires jsonpath, selector or version_regex".to_string(),
+                ))
+            }
+        };
+
+        Ok(Box::new(Self {
+            project_name: project_name.to_string(),
+            url: url.to_string(),
+            extractor,
+            version_regex,
+        }))
+    }
+
+    fn extract(&self, body: &str) -> Result<Vec<Found>, CollectorError> {
+        let found = match &self.extractor {
+            Extractor::Json { version, date } => {
+                let json: serde_json::Value =
+                    serde_json::from_str(body).map_err(|e| CollectorError::Parse(format!("{}: {}", self.url, e)))?;
+                let dates: Vec<Option<String>> = match date {
+                    Some(date) => date.query(&json).all().into_iter().map(json_text).collect(),
+                    None => vec![],
+                };
+                version
+                    .query(&json)
+                    .all()
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| {
+                        Some(Found {
+                            text: json_text(v)?,
+                            date: dates.get(i).cloned().flatten(),
+                            link: None,
+                        })
+                    })
+                    .collect()
+            }
+            Extractor::Html { version, date } => {
+                let html = Html::parse_document(body);
+                let dates: Vec<String> = match date {
+                    Some(date) => html.select(date).map(|e| e.text().collect::<String>()).collect(),
+                    None => vec![],
+                };
+                html.select(version)
+                    .enumerate()
+                    .map(|(i, e)| Found {
+                        text: e.text().collect::<String>(),
+                        date: dates.get(i).cloned(),
+                        link: e.value().attr("href").map(|s| s.to_string()),
+                    })
+                    .collect()
+            }
+            Extractor::Regex => match &self.version_regex {
+                Some(vregex) => vregex
+                    .find_iter(body)
+                    .map(|m| Found {
+                        text: m.as_str().to_string(),
+                        date: None,
+                        link: None,
+                    })
+                    .collect(),
+                None => vec![],
+            },
+        };
+        Ok(found)
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for HttpCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let body = match get_if_modified(ctx, &self.project_name, &self.url, &HeaderMap::new()).await? {
+            Some(res) => res.text().await?,
+            None => return Ok(vec![]),
+        };
+        let found = self.extract(&body)?;
+        debug!("http.found: {:#?}", found);
+
+        let page_url = Url::parse(&self.url).ok();
+        let mut releases = vec![];
+        for f in found.iter() {
+            // link text of download page is often only a file name, try href too
+            let version = match match_version(&self.version_regex, f.text.trim()).or_else(|| {
+                f.link
+                    .as_ref()
+                    .and_then(|link| match_version(&self.version_regex, link))
+            }) {
+                Some(v) if !v.is_empty() => v,
+                _ => continue,
+            };
+            let bump_date = match f.date.as_deref().and_then(parse_loose_date) {
+                Some(d) => d,
+                None => {
+                    if let Some(date) = &f.date {
+                        warn!("unknown date format: {}", date);
+                    }
+                    Utc::now().naive_utc()
+                }
+            };
+            let url = match (&f.link, &page_url) {
+                (Some(link), Some(page_url)) => page_url.join(link).ok().map(|u| u.to_string()),
+                _ => Some(self.url.clone()),
+            };
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version,
+                bump_date,
+                url,
+                yanked: false,
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+}
@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::header::{HeaderMap, LAST_MODIFIED};
+
+use crate::collector::{get_if_modified, CollectedRelease, Collector, CollectorContext, CollectorError};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
+
+/// `<versioning>` of maven-metadata.xml
+#[derive(Debug, Default)]
+struct MavenMetadata {
+    versions: Vec<String>,
+    last_updated: Option<String>,
+}
+
+fn parse_metadata(xml: &str) -> Result<MavenMetadata, CollectorError> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut metadata = MavenMetadata::default();
+    let mut path: Vec<String> = vec![];
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => path.push(String::from_utf8_lossy(e.name().as_ref()).to_string()),
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Text(e)) => {
+                let text = e
+                    .unescape()
+                    .map_err(|e| CollectorError::Parse(format!("maven-metadata.xml: {}", e)))?
+                    .to_string();
+                match path.iter().map(|s| s.as_str()).collect::<Vec<&str>>().as_slice() {
+                    [.., "versioning", "versions", "version"] => metadata.versions.push(text),
+                    [.., "versioning", "lastUpdated"] => metadata.last_updated = Some(text),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(CollectorError::Parse(format!("maven-metadata.xml: {}", e))),
+        }
+    }
+    Ok(metadata)
+}
+
+/// collector for maven repositories (Maven Central, Nexus, Artifactory, ...)
+pub struct MavenCollector {
+    repository_url: String,
+    project_name: String,
+    group_id: String,
+    artifact_id: String,
+}
+
+impl MavenCollector {
+    pub fn new(repository_url: &str, project_name: &str, group_id: &str, artifact_id: &str) -> Self {
+        Self {
+            repository_url: repository_url.to_string(),
+            project_name: project_name.to_string(),
+            group_id: group_id.to_string(),
+            artifact_id: artifact_id.to_string(),
+        }
+    }
+
+    /// `source = { maven = "com.google.guava:guava" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let coordinate = source.maven.as_deref().unwrap_or_default();
+        match coordinate.split_once(':') {
+            Some((group_id, artifact_id)) if !group_id.is_empty() && !artifact_id.is_empty() => {
+                let repository_url = ctx.config.registry_url(source, MAVEN_CENTRAL);
+                Ok(Box::new(Self::new(
+                    &repository_url,
+                    project_name,
+                    group_id,
+                    artifact_id,
+                )))
+            }
+            _ => Err(CollectorError::Config(format!(
+                "invalid maven artifact: {}",
+                coordinate
+            ))),
+        }
+    }
+
+    /// `<repository>/com/google/guava/guava`
+    fn artifact_url(&self) -> String {
+        format!(
+            "{}/{}/{}",
+            self.repository_url,
+            self.group_id.replace('.', "/"),
+            self.artifact_id
+        )
+    }
+
+    /// Last-Modified of the pom file of the version
+    async fn get_pom_date(&self, ctx: &CollectorContext<'_>, version: &str) -> Option<NaiveDateTime> {
+        let url = format!(
+            "{}/{}/{}-{}.pom",
+            self.artifact_url(),
+            version,
+            self.artifact_id,
+            version
+        );
+        debug!("HEAD {}", url);
+        let res = ctx.client.head(url).send().await.ok()?.error_for_status().ok()?;
+        let last_modified = res.headers().get(LAST_MODIFIED)?.to_str().ok()?;
+        DateTime::parse_from_rfc2822(last_modified).ok().map(|d| d.naive_utc())
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for MavenCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let url = format!("{}/maven-metadata.xml", self.artifact_url());
+        let xml = match get_if_modified(ctx, &self.project_name, &url, &HeaderMap::new()).await? {
+            Some(res) => res.text().await?,
+            None => return Ok(vec![]),
+        };
+        let metadata = parse_metadata(&xml)?;
+        debug!("maven.metadata: {:?}", metadata);
+
+        // lastUpdated (`20240102030405`) is the date of the whole artifact, used when the pom has no date
+        let last_updated = match &metadata.last_updated {
+            Some(s) => NaiveDateTime::parse_from_str(s, "%Y%m%d%H%M%S")
+                .map_err(|e| CollectorError::Parse(format!("{}: {}", s, e)))?,
+            None => {
+                return Err(CollectorError::Parse(
+                    "no lastUpdated in maven-metadata.xml".to_string(),
+                ))
+            }
+        };
+
+        let known_versions = ctx.known_versions(&self.project_name);
+        let mut releases = vec![];
+        for version in metadata.versions.iter() {
+            if known_versions.contains(&("".to_string(), version.clone())) {
+                continue;
+            }
+            let bump_date = match self.get_pom_date(ctx, version).await {
+                Some(d) => d,
+                None => {
+                    warn!("not found pom date: {}:{}", self.artifact_id, version);
+                    last_updated
+                }
+            };
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version: version.clone(),
+                bump_date,
+                url: Some(format!("{}/{}/", self.artifact_url(), version)),
+                yanked: false,
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+}
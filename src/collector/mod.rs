@@ -1,2 +1,594 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime};
+use diesel::{Connection, SqliteConnection};
+use regex::Regex;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LINK};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::config::{Config, ProjectConfig, ProjectSourceConfig};
+use crate::database;
+
+pub mod crates_io;
+pub mod feed;
 pub mod git;
+pub mod gitea;
 pub mod github;
+pub mod gitlab;
+pub mod gomod;
+pub mod http;
+pub mod maven;
+pub mod npm;
+pub mod oci;
+pub mod pypi;
+mod signature;
+
+const USER_AGENT: &str = "tamatebako-client";
+
+/// one version found by a collector
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollectedRelease {
+    pub channel: String,
+    pub version: String,
+    pub bump_date: NaiveDateTime,
+    pub url: Option<String>,
+    pub yanked: bool,
+    /// release body or annotated tag message
+    pub release_notes: Option<String>,
+    /// changes since the previous version of the channel (git source)
+    pub changes: Option<ChangeSummary>,
+    /// verification result of the signed tag (git source)
+    pub signature: Option<TagSignature>,
+}
+
+/// verification result of the tag object of a version
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagSignature {
+    pub tag_oid: String,
+    pub status: SignatureStatus,
+}
+
+/// verification result of the signature of a tag
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignatureStatus {
+    Unsigned,
+    /// signed by a trusted key, with the gpg key fingerprint or the ssh principal
+    Good(String),
+    /// the signature does not match the tag
+    Bad,
+    /// signed by a key which is not in the keyring or the allowed signers
+    UnknownKey,
+    /// the signature or the key is expired
+    Expired,
+    /// signed by a revoked key
+    Revoked,
+}
+
+impl SignatureStatus {
+    /// (status, signer) stored in tag_signatures
+    pub fn to_columns(&self) -> (&'static str, Option<&str>) {
+        match self {
+            SignatureStatus::Unsigned => ("unsigned", None),
+            SignatureStatus::Good(signer) => ("good", Some(signer)),
+            SignatureStatus::Bad => ("bad", None),
+            SignatureStatus::UnknownKey => ("unknown_key", None),
+            SignatureStatus::Expired => ("expired", None),
+            SignatureStatus::Revoked => ("revoked", None),
+        }
+    }
+
+    pub fn from_columns(status: &str, signer: Option<String>) -> Option<Self> {
+        match status {
+            "unsigned" => Some(SignatureStatus::Unsigned),
+            "good" => Some(SignatureStatus::Good(signer.unwrap_or_default())),
+            "bad" => Some(SignatureStatus::Bad),
+            "unknown_key" => Some(SignatureStatus::UnknownKey),
+            "expired" => Some(SignatureStatus::Expired),
+            "revoked" => Some(SignatureStatus::Revoked),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignatureStatus::Unsigned => write!(f, "unsigned"),
+            SignatureStatus::Good(signer) => write!(f, "good signature by {}", signer),
+            SignatureStatus::Bad => write!(f, "BAD signature"),
+            SignatureStatus::UnknownKey => write!(f, "signed by unknown key"),
+            SignatureStatus::Expired => write!(f, "signed by expired key"),
+            SignatureStatus::Revoked => write!(f, "signed by REVOKED key"),
+        }
+    }
+}
+
+/// commits and diffstat between two versions
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangeSummary {
+    pub previous_version: String,
+    pub commits: usize,
+    pub contributors: usize,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl fmt::Display for ChangeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} commits, {} contributors, {} files changed, {} insertions(+), {} deletions(-) since {}",
+            self.commits, self.contributors, self.files_changed, self.insertions, self.deletions, self.previous_version
+        )
+    }
+}
+
+impl From<database::VersionChanges> for ChangeSummary {
+    fn from(changes: database::VersionChanges) -> Self {
+        Self {
+            previous_version: changes.previous_version,
+            commits: changes.commits as usize,
+            contributors: changes.contributors as usize,
+            files_changed: changes.files_changed as usize,
+            insertions: changes.insertions as usize,
+            deletions: changes.deletions as usize,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CollectorError {
+    Config(String),
+    Git(git2::Error),
+    Http(reqwest::Error),
+    Io(std::io::Error),
+    Parse(String),
+    /// the project is deferred until the rate limit of the source resets
+    RateLimited(String),
+}
+
+impl fmt::Display for CollectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CollectorError::Config(s) => write!(f, "config error: {}", s),
+            CollectorError::Git(e) => write!(f, "git error: {}", e),
+            CollectorError::Http(e) => write!(f, "http error: {}", e),
+            CollectorError::Io(e) => write!(f, "io error: {}", e),
+            CollectorError::Parse(s) => write!(f, "parse error: {}", s),
+            CollectorError::RateLimited(s) => write!(f, "rate limited: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for CollectorError {}
+
+impl From<git2::Error> for CollectorError {
+    fn from(e: git2::Error) -> Self {
+        CollectorError::Git(e)
+    }
+}
+
+impl From<reqwest::Error> for CollectorError {
+    fn from(e: reqwest::Error) -> Self {
+        CollectorError::Http(e)
+    }
+}
+
+impl From<std::io::Error> for CollectorError {
+    fn from(e: std::io::Error) -> Self {
+        CollectorError::Io(e)
+    }
+}
+
+/// (etag, last_modified) of a response, `None` drops the cached validators of the url
+type Validators = Option<(Option<String>, Option<String>)>;
+
+/// state shared by all collectors during one `check` run
+pub struct CollectorContext<'a> {
+    pub config: &'a Config,
+    pub db_url: String,
+    pub client: Client,
+    pub github: github::GitHubState,
+    /// send conditional requests with the cached `ETag` / `Last-Modified`
+    use_http_cache: bool,
+    /// validators of this run by (project, url), saved after the project is stored
+    validators: RefCell<HashMap<(String, String), Validators>>,
+}
+
+impl<'a> CollectorContext<'a> {
+    pub fn new(config: &'a Config, use_http_cache: bool) -> Self {
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("fail to build http client");
+        Self {
+            config,
+            db_url: config.get_database_url(),
+            client,
+            github: github::GitHubState::default(),
+            use_http_cache,
+            validators: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// add `If-None-Match` / `If-Modified-Since` of the last successful response of the url
+    pub fn conditional(&self, project_name: &str, url: &str, req: RequestBuilder) -> RequestBuilder {
+        if !self.use_http_cache {
+            return req;
+        }
+        let mut dbconn = database::get_database_connection(self.db_url.as_str());
+        match database::get_http_cache(&mut dbconn, project_name, url) {
+            Ok(Some((etag, last_modified))) => {
+                let mut req = req;
+                if let Some(etag) = etag {
+                    req = req.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = last_modified {
+                    req = req.header(IF_MODIFIED_SINCE, last_modified);
+                }
+                req
+            }
+            Ok(None) => req,
+            Err(e) => {
+                error!("select error: {:?}", e);
+                req
+            }
+        }
+    }
+
+    /// remember validators of the response, returns true when the response is `304 Not Modified`
+    pub fn not_modified(&self, project_name: &str, url: &str, res: &Response) -> bool {
+        if res.status() == StatusCode::NOT_MODIFIED {
+            debug!("not modified: {}", url);
+            return true;
+        }
+        if res.status().is_success() {
+            let header = |name| {
+                res.headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string())
+            };
+            let validators = match (header(ETAG), header(LAST_MODIFIED)) {
+                (None, None) => None,
+                validators => Some(validators),
+            };
+            self.validators
+                .borrow_mut()
+                .insert((project_name.to_string(), url.to_string()), validators);
+        }
+        false
+    }
+
+    /// drop validators of the url, for responses which can not tell that nothing is new
+    pub fn forget_validators(&self, project_name: &str, url: &str) {
+        self.validators
+            .borrow_mut()
+            .insert((project_name.to_string(), url.to_string()), None);
+    }
+
+    /// save validators of the project after its releases are stored successfully
+    pub fn save_validators(&self, conn: &mut SqliteConnection, project_name: &str) {
+        let mut validators = self.validators.borrow_mut();
+        let keys: Vec<(String, String)> = validators.keys().filter(|(p, _)| p == project_name).cloned().collect();
+        for key in keys {
+            let (_, url) = &key;
+            let ret = match validators.remove(&key).flatten() {
+                Some((etag, last_modified)) => {
+                    database::save_http_cache(conn, project_name, url, etag.as_deref(), last_modified.as_deref())
+                }
+                None => database::delete_http_cache(conn, project_name, url),
+            };
+            if let Err(e) = ret {
+                error!("http cache error: {:?}", e);
+            }
+        }
+    }
+
+    /// drop validators of the project when its collection failed, so the next run fetches it again
+    pub fn discard_validators(&self, project_name: &str) {
+        self.validators.borrow_mut().retain(|(p, _), _| p != project_name);
+    }
+
+    /// (channel, version) already stored for the project.
+    /// collectors which need a request per version use this to skip known versions.
+    pub fn known_versions(&self, project_name: &str) -> HashSet<(String, String)> {
+        let mut dbconn = database::get_database_connection(self.db_url.as_str());
+        match database::get_project_versions(&mut dbconn, project_name) {
+            Ok(versions) => versions.into_iter().collect(),
+            Err(e) => {
+                error!("select error: {:?}", e);
+                HashSet::new()
+            }
+        }
+    }
+
+    /// tag object id verified for each version of the project
+    pub fn verified_tags(&self, project_name: &str) -> HashMap<String, Option<String>> {
+        let mut dbconn = database::get_database_connection(self.db_url.as_str());
+        match database::get_verified_tags(&mut dbconn, project_name) {
+            Ok(tags) => tags.into_iter().collect(),
+            Err(e) => {
+                error!("select error: {:?}", e);
+                HashMap::new()
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+pub trait Collector {
+    /// fetch all versions of the project from its source
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError>;
+}
+
+pub type CollectorFactory =
+    fn(&CollectorContext, &str, &ProjectConfig, &ProjectSourceConfig) -> Result<Box<dyn Collector>, CollectorError>;
+
+/// collector factories keyed by source type (`git`, `github`, ...)
+pub struct Registry {
+    factories: HashMap<&'static str, CollectorFactory>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, source_type: &'static str, factory: CollectorFactory) {
+        self.factories.insert(source_type, factory);
+    }
+
+    /// returns `None` when the project has no source or no collector is registered for it
+    pub fn build(
+        &self,
+        ctx: &CollectorContext,
+        project_name: &str,
+        project: &ProjectConfig,
+    ) -> Option<Result<Box<dyn Collector>, CollectorError>> {
+        let source = project.source.as_ref()?;
+        let source_type = source.source_type()?;
+        debug!("source type: {}", source_type);
+        match self.factories.get(source_type) {
+            Some(factory) => Some(factory(ctx, project_name, project, source)),
+            None => {
+                error!("not registered source type: {}", source_type);
+                None
+            }
+        }
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register("git", git::GitCollector::factory);
+        registry.register("github", github::GitHubCollector::factory);
+        registry.register("gitlab", gitlab::GitLabCollector::factory);
+        registry.register("gitea", gitea::GiteaCollector::factory);
+        registry.register("crate", crates_io::CratesIoCollector::factory);
+        registry.register("pypi", pypi::PyPICollector::factory);
+        registry.register("npm", npm::NpmCollector::factory);
+        registry.register("gomod", gomod::GoModCollector::factory);
+        registry.register("image", oci::OciCollector::factory);
+        registry.register("maven", maven::MavenCollector::factory);
+        registry.register("feed", feed::FeedCollector::factory);
+        registry.register("http", http::HttpCollector::factory);
+        registry
+    }
+}
+
+pub fn compile_version_regex(project: &ProjectConfig) -> Result<Option<Regex>, CollectorError> {
+    match &project.version_regex {
+        Some(s) => Regex::new(s)
+            .map(Some)
+            .map_err(|e| CollectorError::Config(format!("invalid version_regex: {}", e))),
+        None => Ok(None),
+    }
+}
+
+/// version in the text, the first capture group of `version_regex` or the whole match.
+/// returns the text itself when there is no `version_regex`.
+pub fn match_version(version_regex: &Option<Regex>, text: &str) -> Option<String> {
+    match version_regex {
+        Some(vregex) => {
+            let caps = vregex.captures(text)?;
+            caps.get(1).or_else(|| caps.get(0)).map(|m| m.as_str().to_string())
+        }
+        None => Some(text.to_string()),
+    }
+}
+
+/// parse RFC 3339 date time (`2019-01-03T01:56:19.539Z`) as UTC
+pub fn parse_datetime(date: &str) -> Result<NaiveDateTime, CollectorError> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|d| d.naive_utc())
+        .map_err(|e| CollectorError::Parse(format!("{}: {}", date, e)))
+}
+
+/// release body or tag message as release notes, `None` when it is blank
+pub fn release_notes(text: Option<&str>) -> Option<String> {
+    let text = text?.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+/// url of `rel="next"` in the Link header
+pub fn next_link(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        if params.split(';').any(|p| p.trim() == "rel=\"next\"") {
+            Some(url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// conditional GET, returns `None` when the server answers `304 Not Modified`
+pub async fn get_if_modified(
+    ctx: &CollectorContext<'_>,
+    project_name: &str,
+    url: &str,
+    headers: &HeaderMap,
+) -> Result<Option<Response>, CollectorError> {
+    debug!("GET {}", url);
+    let req = ctx.conditional(project_name, url, ctx.client.get(url).headers(headers.clone()));
+    let res = req.send().await?;
+    if ctx.not_modified(project_name, url, &res) {
+        return Ok(None);
+    }
+    Ok(Some(res.error_for_status()?))
+}
+
+/// `get_all_pages` with conditional GET of the first page.
+/// the list must be ordered newest first, unchanged first page means no new item.
+pub async fn get_all_pages_if_modified<T: DeserializeOwned>(
+    ctx: &CollectorContext<'_>,
+    project_name: &str,
+    url: &str,
+    headers: &HeaderMap,
+) -> Result<Option<Vec<T>>, CollectorError> {
+    let res = match get_if_modified(ctx, project_name, url, headers).await? {
+        Some(res) => res,
+        None => return Ok(None),
+    };
+    let next_url = next_link(res.headers());
+    let mut items: Vec<T> = res.json().await?;
+    if let Some(next_url) = next_url {
+        items.append(&mut get_all_pages(ctx, &next_url, headers).await?);
+    }
+    Ok(Some(items))
+}
+
+/// GET json array and follow Link headers through all pages
+pub async fn get_all_pages<T: DeserializeOwned>(
+    ctx: &CollectorContext<'_>,
+    url: &str,
+    headers: &HeaderMap,
+) -> Result<Vec<T>, CollectorError> {
+    let mut items = vec![];
+    let mut next_url = Some(url.to_string());
+    while let Some(url) = next_url {
+        debug!("GET {}", url);
+        let res = ctx
+            .client
+            .get(url.as_str())
+            .headers(headers.clone())
+            .send()
+            .await?
+            .error_for_status()?;
+        next_url = next_link(res.headers());
+        let mut page: Vec<T> = res.json().await?;
+        items.append(&mut page);
+    }
+    Ok(items)
+}
+
+/// insert collected releases into version_history, returns the number of new versions.
+/// all rows of the project are written in one transaction.
+pub fn store(conn: &mut SqliteConnection, project_name: &str, releases: &[CollectedRelease]) -> usize {
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let mut stored_notes: HashMap<String, String> = database::get_project_release_notes(conn, project_name)
+            .unwrap_or_else(|e| {
+                error!("select error: {:?}", e);
+                vec![]
+            })
+            .into_iter()
+            .collect();
+        let mut insert_num = 0;
+        for release in releases {
+            let version_history = database::VersionHistory {
+                id: 0,
+                project_name: project_name.to_string(),
+                channel: release.channel.clone(),
+                version: release.version.clone(),
+                bump_date: release.bump_date,
+                url: release.url.clone(),
+                yanked: release.yanked,
+            };
+
+            match database::insert_version_history(conn, &version_history) {
+                Ok(0) => {
+                    match database::update_yanked(
+                        conn,
+                        project_name,
+                        &release.channel,
+                        &release.version,
+                        release.yanked,
+                    ) {
+                        Ok(0) => {}
+                        Ok(_) => info!("update yanked. {:?}", version_history),
+                        Err(e) => error!("update error: {:?}", e),
+                    }
+                }
+                Ok(n) => {
+                    info!("insert data. {:?}", version_history);
+                    insert_num += n;
+                }
+                Err(e) => error!("insert error: {:?}", e),
+            }
+
+            // the collectors return the notes of all versions at every check, only new or edited ones are saved
+            if let Some(notes) = &release.release_notes {
+                if stored_notes.get(&release.version) != Some(notes) {
+                    match database::save_release_notes(conn, project_name, &release.version, notes) {
+                        Ok(_) => {
+                            stored_notes.insert(release.version.clone(), notes.clone());
+                        }
+                        Err(e) => error!("save release notes error: {:?}", e),
+                    }
+                }
+            }
+
+            if let Some(changes) = &release.changes {
+                let version_changes = database::VersionChanges {
+                    project_name: project_name.to_string(),
+                    channel: release.channel.clone(),
+                    version: release.version.clone(),
+                    previous_version: changes.previous_version.clone(),
+                    commits: changes.commits as i32,
+                    contributors: changes.contributors as i32,
+                    files_changed: changes.files_changed as i32,
+                    insertions: changes.insertions as i32,
+                    deletions: changes.deletions as i32,
+                };
+                if let Err(e) = database::save_version_changes(conn, &version_changes) {
+                    error!("save version changes error: {:?}", e);
+                }
+            }
+
+            if let Some(signature) = &release.signature {
+                let (status, signer) = signature.status.to_columns();
+                if let Err(e) = database::save_tag_signature(
+                    conn,
+                    project_name,
+                    &release.version,
+                    status,
+                    signer,
+                    &signature.tag_oid,
+                ) {
+                    error!("save tag signature error: {:?}", e);
+                }
+            }
+        }
+        Ok(insert_num)
+    });
+    match result {
+        Ok(n) => n,
+        Err(e) => {
+            error!("store error: {:?}", e);
+            0
+        }
+    }
+}
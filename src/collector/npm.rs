@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+
+use crate::collector::{
+    get_if_modified, parse_datetime, CollectedRelease, Collector, CollectorContext, CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+const NPM_WEB: &str = "https://www.npmjs.com";
+const LATEST_TAG: &str = "latest";
+
+/// package document (`/<package>`)
+#[derive(Debug, Deserialize)]
+struct Packument {
+    #[serde(rename = "dist-tags", default)]
+    dist_tags: HashMap<String, String>,
+    #[serde(default)]
+    versions: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    time: HashMap<String, String>,
+}
+
+pub struct NpmCollector {
+    project_name: String,
+    registry_url: String,
+    package_name: String,
+}
+
+/// prerelease identifier of the version (`2.0.0-beta.1` -> `beta`)
+fn prerelease_id(version: &str) -> Option<&str> {
+    let (_, pre) = version.split_once('-')?;
+    let pre = pre.split('+').next().unwrap_or_default();
+    let id = pre
+        .split(|c: char| c == '.' || c.is_ascii_digit())
+        .next()
+        .unwrap_or_default();
+    Some(id)
+}
+
+/// channel of the version, `latest` for stable versions and the prerelease identifier
+/// (`2.0.0-beta.1` -> `beta`) for prerelease versions.
+///
+/// the channel is a part of the stored key of the version, so it does not follow the dist-tags,
+/// which move to another version at every release.
+fn channel_of(version: &str) -> String {
+    match prerelease_id(version) {
+        None => LATEST_TAG.to_string(),
+        Some("") => "prerelease".to_string(),
+        Some(id) => id.to_string(),
+    }
+}
+
+impl NpmCollector {
+    pub fn new(project_name: &str, registry_url: &str, package_name: &str) -> Self {
+        Self {
+            project_name: project_name.to_string(),
+            registry_url: registry_url.to_string(),
+            package_name: package_name.to_string(),
+        }
+    }
+
+    /// `source = { npm = "@scope/package" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let package_name = source.npm.as_deref().unwrap_or_default();
+        if package_name.is_empty() {
+            return Err(CollectorError::Config("empty npm package name".to_string()));
+        }
+        let registry_url = ctx.config.registry_url(source, NPM_REGISTRY);
+        Ok(Box::new(Self::new(project_name, &registry_url, package_name)))
+    }
+
+    fn releases(&self, packument: &Packument) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let mut releases = vec![];
+        for version in packument.versions.keys() {
+            let bump_date = match packument.time.get(version) {
+                Some(t) => parse_datetime(t)?,
+                None => {
+                    warn!("not found publish time: {} {}", self.package_name, version);
+                    Utc::now().naive_utc()
+                }
+            };
+            releases.push(CollectedRelease {
+                channel: channel_of(version),
+                version: version.clone(),
+                bump_date,
+                url: self.version_url(version),
+                yanked: false,
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+
+    fn version_url(&self, version: &str) -> Option<String> {
+        if self.registry_url == NPM_REGISTRY {
+            Some(format!("{}/package/{}/v/{}", NPM_WEB, self.package_name, version))
+        } else {
+            None
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for NpmCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        // scoped package is `@scope%2fpackage`
+        let url = format!("{}/{}", self.registry_url, self.package_name.replace('/', "%2f"));
+        let packument: Packument = match get_if_modified(ctx, &self.project_name, &url, &HeaderMap::new()).await? {
+            Some(res) => res.json().await?,
+            None => return Ok(vec![]),
+        };
+        debug!("npm.dist-tags: {:?}", packument.dist_tags);
+        self.releases(&packument)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn stored_keys(packument: &str) -> HashSet<(String, String)> {
+        let packument: Packument = serde_json::from_str(packument).unwrap();
+        NpmCollector::new("pkg", NPM_REGISTRY, "pkg")
+            .releases(&packument)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.channel, r.version))
+            .collect()
+    }
+
+    #[test]
+    fn channel_of_version() {
+        assert_eq!(channel_of("3.9.1"), "latest");
+        assert_eq!(channel_of("3.9.1+build.5"), "latest");
+        assert_eq!(channel_of("4.0.0-rc.1"), "rc");
+        assert_eq!(channel_of("4.0.0-beta2"), "beta");
+        assert_eq!(channel_of("4.0.0-0"), "prerelease");
+    }
+
+    #[test]
+    fn channel_does_not_follow_moving_dist_tags() {
+        let before = r#"{
+            "dist-tags": {"latest": "4.0.0", "latest-3": "3.9.1", "next": "5.0.0-rc.1"},
+            "versions": {"3.9.1": {}, "4.0.0": {}, "5.0.0-rc.1": {}},
+            "time": {
+                "3.9.1": "2024-01-01T00:00:00.000Z",
+                "4.0.0": "2024-02-01T00:00:00.000Z",
+                "5.0.0-rc.1": "2024-03-01T00:00:00.000Z"
+            }
+        }"#;
+        let after = r#"{
+            "dist-tags": {"latest": "4.0.0", "latest-3": "3.9.2", "next": "5.0.0-beta.1"},
+            "versions": {"3.9.1": {}, "3.9.2": {}, "4.0.0": {}, "5.0.0-rc.1": {}, "5.0.0-beta.1": {}},
+            "time": {
+                "3.9.1": "2024-01-01T00:00:00.000Z",
+                "3.9.2": "2024-04-01T00:00:00.000Z",
+                "4.0.0": "2024-02-01T00:00:00.000Z",
+                "5.0.0-rc.1": "2024-03-01T00:00:00.000Z",
+                "5.0.0-beta.1": "2024-04-02T00:00:00.000Z"
+            }
+        }"#;
+
+        let before = stored_keys(before);
+        let after = stored_keys(after);
+        assert!(before.is_subset(&after), "{:?} is stored again in {:?}", before, after);
+        let new: HashSet<(String, String)> = after.difference(&before).cloned().collect();
+        let expected: HashSet<(String, String)> = [("latest", "3.9.2"), ("beta", "5.0.0-beta.1")]
+            .iter()
+            .map(|(c, v)| (c.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(new, expected);
+    }
+}
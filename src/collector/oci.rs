@@ -0,0 +1,339 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use regex::Regex;
+use reqwest::header::{ACCEPT, WWW_AUTHENTICATE};
+use reqwest::{Response, StatusCode};
+use url::Url;
+
+use crate::collector::{
+    compile_version_regex, match_version, next_link, parse_datetime, CollectedRelease, Collector, CollectorContext,
+    CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+const DOCKER_HUB: &str = "docker.io";
+const DOCKER_HUB_API: &str = "https://registry-1.docker.io";
+const DOCKER_HUB_WEB: &str = "https://hub.docker.com";
+
+/// images resolved for the created time in a check, the rest are resolved at the next check
+const MAX_NEW_TAGS: usize = 100;
+
+const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.index.v1+json, \
+application/vnd.oci.image.manifest.v1+json, \
+application/vnd.docker.distribution.manifest.list.v2+json, \
+application/vnd.docker.distribution.manifest.v2+json";
+
+lazy_static! {
+    static ref RE_AUTH_PARAM: Regex = Regex::new(r#"(\w+)="([^"]*)""#).unwrap();
+}
+
+#[derive(Debug, Deserialize)]
+struct TagList {
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Platform {
+    architecture: String,
+    os: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Descriptor {
+    digest: String,
+    platform: Option<Platform>,
+}
+
+/// image manifest or image index (manifest list)
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    config: Option<Descriptor>,
+    manifests: Option<Vec<Descriptor>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageConfig {
+    created: Option<String>,
+}
+
+#[derive(Debug)]
+enum Auth {
+    Anonymous,
+    Basic,
+    Bearer(String),
+}
+
+/// collector for container image tags via OCI distribution API
+pub struct OciCollector {
+    api_url: String,
+    repository: String,
+    project_name: String,
+    version_regex: Option<Regex>,
+    username: Option<String>,
+    password: Option<String>,
+    auth: Auth,
+    web_url: Option<String>,
+}
+
+/// split image reference into registry host and repository name (`postgres` -> `docker.io`, `library/postgres`)
+fn parse_image(image: &str) -> (&str, String) {
+    match image.split_once('/') {
+        Some((host, name)) if host.contains('.') || host.contains(':') || host == "localhost" => {
+            if host == DOCKER_HUB && !name.contains('/') {
+                (DOCKER_HUB, format!("library/{}", name))
+            } else {
+                (host, name.to_string())
+            }
+        }
+        Some(_) => (DOCKER_HUB, image.to_string()),
+        None => (DOCKER_HUB, format!("library/{}", image)),
+    }
+}
+
+impl OciCollector {
+    /// `source = { image = "docker.io/library/postgres" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let image = source.image.as_deref().unwrap_or_default();
+        if image.is_empty() {
+            return Err(CollectorError::Config("empty image name".to_string()));
+        }
+        let (host, repository) = parse_image(image.trim_end_matches('/'));
+        let registry = ctx.config.oci.get(host).cloned().unwrap_or_default();
+        let api_url = match (&registry.url, host) {
+            (None, DOCKER_HUB) => DOCKER_HUB_API.to_string(),
+            _ => registry.base_url(host),
+        };
+        let web_url = if host == DOCKER_HUB {
+            Some(format!("{}/r/{}/tags", DOCKER_HUB_WEB, repository))
+        } else {
+            None
+        };
+        Ok(Box::new(Self {
+            api_url,
+            repository,
+            project_name: project_name.to_string(),
+            version_regex: compile_version_regex(project)?,
+            username: registry.username,
+            password: registry.token,
+            auth: Auth::Anonymous,
+            web_url,
+        }))
+    }
+
+    /// get bearer token from the realm of `WWW-Authenticate` challenge
+    async fn authenticate(&mut self, ctx: &CollectorContext<'_>, challenge: &str) -> Result<(), CollectorError> {
+        let (scheme, params) = challenge.split_once(' ').unwrap_or((challenge, ""));
+        if scheme.eq_ignore_ascii_case("basic") {
+            self.auth = Auth::Basic;
+            return Ok(());
+        }
+
+        let mut realm = None;
+        let mut query = vec![];
+        for caps in RE_AUTH_PARAM.captures_iter(params) {
+            match &caps[1] {
+                "realm" => realm = Some(caps[2].to_string()),
+                key => query.push((key.to_string(), caps[2].to_string())),
+            }
+        }
+        let realm = realm.ok_or_else(|| CollectorError::Parse(format!("no realm in challenge: {}", challenge)))?;
+
+        debug!("GET {}", realm);
+        let mut req = ctx.client.get(realm).query(&query);
+        if let Some(username) = &self.username {
+            req = req.basic_auth(username, self.password.as_ref());
+        }
+        let res: TokenResponse = req.send().await?.error_for_status()?.json().await?;
+        match res.token.or(res.access_token) {
+            Some(token) => {
+                self.auth = Auth::Bearer(token);
+                Ok(())
+            }
+            None => Err(CollectorError::Parse("no token in token response".to_string())),
+        }
+    }
+
+    /// GET with registry authentication, retry once after the `401 Unauthorized` challenge.
+    /// `conditional` adds the validators of the last check of the url.
+    async fn get(
+        &mut self,
+        ctx: &CollectorContext<'_>,
+        url: &str,
+        accept: Option<&str>,
+        conditional: bool,
+    ) -> Result<Response, CollectorError> {
+        for attempt in 0..2 {
+            debug!("GET {}", url);
+            let mut req = ctx.client.get(url);
+            if let Some(accept) = accept {
+                req = req.header(ACCEPT, accept);
+            }
+            if conditional {
+                req = ctx.conditional(&self.project_name, url, req);
+            }
+            req = match &self.auth {
+                Auth::Anonymous => req,
+                Auth::Basic => match &self.username {
+                    Some(username) => req.basic_auth(username, self.password.as_ref()),
+                    None => req,
+                },
+                Auth::Bearer(token) => req.bearer_auth(token),
+            };
+            let res = req.send().await?;
+            // bearer token expires during the long run, so authenticate again on every first `401`
+            if res.status() != StatusCode::UNAUTHORIZED || attempt > 0 {
+                return Ok(res.error_for_status()?);
+            }
+            let challenge = match res.headers().get(WWW_AUTHENTICATE).and_then(|v| v.to_str().ok()) {
+                Some(c) => c.to_string(),
+                None => return Ok(res.error_for_status()?),
+            };
+            self.authenticate(ctx, &challenge).await?;
+        }
+        Err(CollectorError::Config(format!("fail to authenticate: {}", url)))
+    }
+
+    fn tags_url(&self) -> String {
+        format!("{}/v2/{}/tags/list?n=1000", self.api_url, self.repository)
+    }
+
+    /// `None` when the tag list is not modified since the last check
+    async fn get_tags(&mut self, ctx: &CollectorContext<'_>) -> Result<Option<Vec<String>>, CollectorError> {
+        let base = Url::parse(&self.api_url).map_err(|e| CollectorError::Config(format!("{}: {}", self.api_url, e)))?;
+        let tags_url = self.tags_url();
+        let mut tags = vec![];
+        let mut next_url = Some(tags_url.clone());
+        while let Some(url) = next_url {
+            let first_page = url == tags_url;
+            let res = self.get(ctx, &url, None, first_page).await?;
+            if first_page && ctx.not_modified(&self.project_name, &url, &res) {
+                return Ok(None);
+            }
+            // Link header of the registry is relative (`</v2/<name>/tags/list?last=...>`)
+            next_url = next_link(res.headers()).and_then(|link| base.join(&link).ok().map(|u| u.to_string()));
+            if first_page && next_url.is_some() {
+                // tags are ordered lexically, a new tag may be on the other page
+                ctx.forget_validators(&self.project_name, &url);
+            }
+            let page: TagList = res.json().await?;
+            tags.append(&mut page.tags.unwrap_or_default());
+        }
+        Ok(Some(tags))
+    }
+
+    async fn get_manifest(&mut self, ctx: &CollectorContext<'_>, reference: &str) -> Result<Manifest, CollectorError> {
+        let url = format!("{}/v2/{}/manifests/{}", self.api_url, self.repository, reference);
+        Ok(self.get(ctx, &url, Some(MANIFEST_ACCEPT), false).await?.json().await?)
+    }
+
+    /// `created` of the image config, linux/amd64 image is used for multi-platform images
+    async fn get_created(&mut self, ctx: &CollectorContext<'_>, tag: &str) -> Result<Option<String>, CollectorError> {
+        let mut manifest = self.get_manifest(ctx, tag).await?;
+        if let Some(manifests) = &manifest.manifests {
+            let image = manifests
+                .iter()
+                .find(|m| matches!(&m.platform, Some(p) if p.os == "linux" && p.architecture == "amd64"))
+                .or_else(|| manifests.first());
+            let digest = match image {
+                Some(m) => m.digest.clone(),
+                None => return Ok(None),
+            };
+            manifest = self.get_manifest(ctx, &digest).await?;
+        }
+        let digest = match &manifest.config {
+            Some(config) => config.digest.clone(),
+            None => return Ok(None),
+        };
+
+        let url = format!("{}/v2/{}/blobs/{}", self.api_url, self.repository, digest);
+        let config: ImageConfig = self.get(ctx, &url, None, false).await?.json().await?;
+        Ok(config.created)
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for OciCollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let tags = match self.get_tags(ctx).await? {
+            Some(tags) => tags,
+            None => return Ok(vec![]),
+        };
+        debug!("oci.tags: {:?}", tags);
+
+        let known_versions = ctx.known_versions(&self.project_name);
+        let mut releases = vec![];
+        // the tags not resolved in this check are tried again with the whole tag list
+        let mut pending = false;
+        for tag in tags.iter() {
+            let version = match match_version(&self.version_regex, tag) {
+                Some(v) if !v.is_empty() => v,
+                _ => continue,
+            };
+            if known_versions.contains(&("".to_string(), version.clone())) {
+                continue;
+            }
+            if releases.len() >= MAX_NEW_TAGS {
+                info!("{}: resolve the rest of new tags at the next check", self.repository);
+                pending = true;
+                break;
+            }
+
+            let created = match self.get_created(ctx, tag).await {
+                Ok(created) => created,
+                // the pull rate limit of Docker Hub, the other requests fail too
+                Err(CollectorError::Http(e)) if e.status() == Some(StatusCode::TOO_MANY_REQUESTS) => {
+                    warn!(
+                        "{}: rate limited, resolve the rest of new tags at the next check",
+                        self.repository
+                    );
+                    pending = true;
+                    break;
+                }
+                Err(e) => {
+                    warn!("get created time of image error: {}:{}: {:?}", self.repository, tag, e);
+                    pending = true;
+                    continue;
+                }
+            };
+            let bump_date = match created.as_deref().map(parse_datetime) {
+                Some(Ok(bump_date)) => bump_date,
+                Some(Err(e)) => {
+                    warn!(
+                        "parse created time of image error: {}:{}: {:?}",
+                        self.repository, tag, e
+                    );
+                    Utc::now().naive_utc()
+                }
+                None => {
+                    warn!("not found created time of image: {}:{}", self.repository, tag);
+                    Utc::now().naive_utc()
+                }
+            };
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version,
+                bump_date,
+                url: self.web_url.as_ref().map(|u| format!("{}?name={}", u, tag)),
+                yanked: false,
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
+        }
+        if pending {
+            ctx.forget_validators(&self.project_name, &self.tags_url());
+        }
+        Ok(releases)
+    }
+}
@@ -0,0 +1,227 @@
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use regex::Regex;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use std::collections::HashMap;
+
+use crate::collector::{
+    get_if_modified, parse_datetime, CollectedRelease, Collector, CollectorContext, CollectorError,
+};
+use crate::config::{ProjectConfig, ProjectSourceConfig};
+
+const PYPI_JSON_API: &str = "https://pypi.org/pypi";
+const SIMPLE_JSON_CONTENT_TYPE: &str = "application/vnd.pypi.simple.v1+json";
+
+lazy_static! {
+    static ref RE_NAME_SEPARATOR: Regex = Regex::new(r"[-_.]+").unwrap();
+}
+
+#[derive(Debug, Deserialize)]
+struct PyPIInfo {
+    package_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PyPIReleaseFile {
+    upload_time_iso_8601: Option<String>,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// response of JSON API (`/pypi/<project>/json`)
+#[derive(Debug, Deserialize)]
+struct PyPIProject {
+    info: PyPIInfo,
+    releases: HashMap<String, Vec<PyPIReleaseFile>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimpleFile {
+    filename: String,
+    #[serde(rename = "upload-time")]
+    upload_time: Option<String>,
+    /// `true` or the reason of yank
+    #[serde(default)]
+    yanked: serde_json::Value,
+}
+
+/// response of Simple API (PEP 691)
+#[derive(Debug, Deserialize)]
+struct SimpleProject {
+    files: Vec<SimpleFile>,
+}
+
+/// earliest upload time and yanked state of the files of one version
+#[derive(Debug, Default)]
+struct VersionFiles {
+    upload_time: Option<NaiveDateTime>,
+    files: usize,
+    yanked_files: usize,
+}
+
+impl VersionFiles {
+    fn add(&mut self, upload_time: Option<&str>, yanked: bool) -> Result<(), CollectorError> {
+        if let Some(t) = upload_time {
+            let t = parse_datetime(t)?;
+            if self.upload_time.is_none_or(|u| t < u) {
+                self.upload_time = Some(t);
+            }
+        }
+        self.files += 1;
+        if yanked {
+            self.yanked_files += 1;
+        }
+        Ok(())
+    }
+
+    /// a version is yanked when all of its files are yanked
+    fn is_yanked(&self) -> bool {
+        self.files > 0 && self.files == self.yanked_files
+    }
+}
+
+pub struct PyPICollector {
+    project_name: String,
+    index_url: String,
+    package_name: String,
+}
+
+/// normalized project name (PEP 503)
+fn normalize_name(name: &str) -> String {
+    RE_NAME_SEPARATOR.replace_all(name, "-").to_lowercase()
+}
+
+/// version part of sdist, wheel and egg file names
+fn version_from_filename(filename: &str) -> Option<String> {
+    if filename.ends_with(".whl") || filename.ends_with(".egg") {
+        return filename.split('-').nth(1).map(|s| s.to_string());
+    }
+    let stem = [".tar.gz", ".tar.bz2", ".tgz", ".zip"]
+        .iter()
+        .find_map(|ext| filename.strip_suffix(ext))?;
+    stem.rsplit_once('-').map(|(_, version)| version.to_string())
+}
+
+impl PyPICollector {
+    pub fn new(project_name: &str, index_url: &str, package_name: &str) -> Self {
+        Self {
+            project_name: project_name.to_string(),
+            index_url: index_url.to_string(),
+            package_name: package_name.to_string(),
+        }
+    }
+
+    /// `source = { pypi = "requests" }`
+    pub fn factory(
+        ctx: &CollectorContext,
+        project_name: &str,
+        _project: &ProjectConfig,
+        source: &ProjectSourceConfig,
+    ) -> Result<Box<dyn Collector>, CollectorError> {
+        let package_name = source.pypi.as_deref().unwrap_or_default();
+        if package_name.is_empty() {
+            return Err(CollectorError::Config("empty pypi project name".to_string()));
+        }
+        let index_url = ctx.config.registry_url(source, PYPI_JSON_API);
+        Ok(Box::new(Self::new(project_name, &index_url, package_name)))
+    }
+
+    /// Simple API index url ends with `/simple` (`https://pypi.org/simple`, `.../+simple`)
+    fn is_simple_api(&self) -> bool {
+        self.index_url.ends_with("simple")
+    }
+
+    /// `None` when the project is not modified since the last check
+    async fn get_json_api(
+        &self,
+        ctx: &CollectorContext<'_>,
+    ) -> Result<Option<(HashMap<String, VersionFiles>, Option<String>)>, CollectorError> {
+        let url = format!("{}/{}/json", self.index_url, self.package_name);
+        let res: PyPIProject = match get_if_modified(ctx, &self.project_name, &url, &HeaderMap::new()).await? {
+            Some(res) => res.json().await?,
+            None => return Ok(None),
+        };
+
+        let mut versions: HashMap<String, VersionFiles> = HashMap::new();
+        for (version, files) in res.releases.iter() {
+            if files.is_empty() {
+                debug!("no files: {} {}", self.package_name, version);
+                continue;
+            }
+            let v = versions.entry(version.clone()).or_default();
+            for file in files {
+                v.add(file.upload_time_iso_8601.as_deref(), file.yanked)?;
+            }
+        }
+        Ok(Some((versions, res.info.package_url)))
+    }
+
+    /// `None` when the project is not modified since the last check
+    async fn get_simple_api(
+        &self,
+        ctx: &CollectorContext<'_>,
+    ) -> Result<Option<HashMap<String, VersionFiles>>, CollectorError> {
+        let url = format!("{}/{}/", self.index_url, normalize_name(&self.package_name));
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(SIMPLE_JSON_CONTENT_TYPE));
+        let res: SimpleProject = match get_if_modified(ctx, &self.project_name, &url, &headers).await? {
+            Some(res) => res.json().await?,
+            None => return Ok(None),
+        };
+
+        let mut versions: HashMap<String, VersionFiles> = HashMap::new();
+        for file in res.files.iter() {
+            let version = match version_from_filename(&file.filename) {
+                Some(v) => v,
+                None => {
+                    debug!("unknown file name format: {}", file.filename);
+                    continue;
+                }
+            };
+            let yanked = !matches!(file.yanked, serde_json::Value::Null | serde_json::Value::Bool(false));
+            versions
+                .entry(version)
+                .or_default()
+                .add(file.upload_time.as_deref(), yanked)?;
+        }
+        Ok(Some(versions))
+    }
+}
+
+#[async_trait(?Send)]
+impl Collector for PyPICollector {
+    async fn collect(&mut self, ctx: &CollectorContext<'_>) -> Result<Vec<CollectedRelease>, CollectorError> {
+        let project = if self.is_simple_api() {
+            self.get_simple_api(ctx).await?.map(|versions| (versions, None))
+        } else {
+            self.get_json_api(ctx).await?
+        };
+        let (versions, package_url) = match project {
+            Some(project) => project,
+            None => return Ok(vec![]),
+        };
+        debug!("pypi.versions: {:#?}", versions);
+
+        let mut releases = vec![];
+        for (version, files) in versions.iter() {
+            let bump_date = match files.upload_time {
+                Some(t) => t,
+                None => {
+                    warn!("not found upload time: {} {}", self.package_name, version);
+                    Utc::now().naive_utc()
+                }
+            };
+            releases.push(CollectedRelease {
+                channel: "".to_string(),
+                version: version.clone(),
+                bump_date,
+                url: package_url.as_ref().map(|u| format!("{}{}/", u, version)),
+                yanked: files.is_yanked(),
+                release_notes: None,
+                changes: None,
+                signature: None,
+            });
+        }
+        Ok(releases)
+    }
+}
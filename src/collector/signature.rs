@@ -0,0 +1,234 @@
+use git2::{Oid, Repository};
+use std::env;
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::collector::{CollectorError, SignatureStatus};
+
+const PGP_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----";
+const SSH_SIGNATURE: &str = "-----BEGIN SSH SIGNATURE-----";
+
+/// keys trusted for the signed tags of a git source
+#[derive(Debug, Default)]
+pub struct SignatureVerifier {
+    /// gpg keyring (`gpg --export KEYID > keyring.gpg`)
+    pub gpg_keyring: Option<String>,
+    /// ssh allowed signers file (`principal key-type key`)
+    pub allowed_signers: Option<String>,
+}
+
+/// start of the signature appended to the message, the last marker at the beginning of a line
+fn signature_start(data: &[u8]) -> Option<usize> {
+    [PGP_SIGNATURE, SSH_SIGNATURE]
+        .iter()
+        .filter_map(|marker| {
+            let marker = marker.as_bytes();
+            (0..data.len())
+                .rev()
+                .find(|&i| data[i..].starts_with(marker) && (i == 0 || data[i - 1] == b'\n'))
+        })
+        .max()
+}
+
+/// (signed payload, signature) of the tag object, the signature is appended to the message
+pub fn split_signature(text: &str) -> (&str, Option<&str>) {
+    match signature_start(text.as_bytes()) {
+        Some(i) => (&text[..i], Some(&text[i..])),
+        None => (text, None),
+    }
+}
+
+impl SignatureVerifier {
+    pub fn is_enabled(&self) -> bool {
+        self.gpg_keyring.is_some() || self.allowed_signers.is_some()
+    }
+
+    /// verify the signature of the tag, lightweight tags are unsigned.
+    /// the payload is verified as raw bytes, the message may be in any `encoding`.
+    pub fn verify_tag(&self, repo: &Repository, oid: Oid) -> Result<SignatureStatus, CollectorError> {
+        if repo.find_tag(oid).is_err() {
+            return Ok(SignatureStatus::Unsigned);
+        }
+        let odb = repo.odb()?;
+        let object = odb.read(oid)?;
+        let data = object.data();
+        let (payload, signature) = match signature_start(data) {
+            Some(i) => (&data[..i], &data[i..]),
+            None => return Ok(SignatureStatus::Unsigned),
+        };
+        if signature.starts_with(PGP_SIGNATURE.as_bytes()) {
+            match &self.gpg_keyring {
+                Some(keyring) => self.verify_gpg(keyring, payload, signature),
+                None => Ok(SignatureStatus::UnknownKey),
+            }
+        } else {
+            match &self.allowed_signers {
+                Some(allowed_signers) => self.verify_ssh(allowed_signers, payload, signature),
+                None => Ok(SignatureStatus::UnknownKey),
+            }
+        }
+    }
+
+    /// `gpgv` with only the keyring and an empty home directory, so `gpg.conf` of the user
+    /// (`auto-key-retrieve`, ...) is not used. the fingerprint of `VALIDSIG` is the signer.
+    fn verify_gpg(&self, keyring: &str, payload: &[u8], signature: &[u8]) -> Result<SignatureStatus, CollectorError> {
+        // gpgv looks for a keyring without slash in its home directory
+        let keyring = fs::canonicalize(keyring)?;
+        let (success, output) = with_signature_file(signature, |dir, path| {
+            run(
+                Command::new("gpgv")
+                    .arg("--homedir")
+                    .arg(dir)
+                    .arg("--keyring")
+                    .arg(&keyring)
+                    .args(["--status-fd", "1", path, "-"]),
+                payload,
+            )
+        })?;
+
+        let mut keywords = vec![];
+        let mut fingerprint = None;
+        for line in output.lines() {
+            let mut fields = line.split_whitespace();
+            if fields.next() != Some("[GNUPG:]") {
+                continue;
+            }
+            match fields.next() {
+                Some("VALIDSIG") => fingerprint = fields.next().map(|f| f.to_string()),
+                Some(keyword) => keywords.push(keyword.to_string()),
+                None => {}
+            }
+        }
+        let has = |keyword: &str| keywords.iter().any(|k| k == keyword);
+
+        // VALIDSIG is printed with REVKEYSIG, EXPKEYSIG and EXPSIG too
+        let status = if has("BADSIG") {
+            SignatureStatus::Bad
+        } else if has("REVKEYSIG") {
+            SignatureStatus::Revoked
+        } else if has("EXPKEYSIG") || has("EXPSIG") {
+            SignatureStatus::Expired
+        } else if has("NO_PUBKEY") {
+            SignatureStatus::UnknownKey
+        } else {
+            match fingerprint {
+                Some(fingerprint) if success && has("GOODSIG") && !has("ERRSIG") => SignatureStatus::Good(fingerprint),
+                _ => SignatureStatus::Bad,
+            }
+        };
+        Ok(status)
+    }
+
+    /// `ssh-keygen -Y find-principals` and then `-Y verify` with the found principal, like git
+    fn verify_ssh(
+        &self,
+        allowed_signers: &str,
+        payload: &[u8],
+        signature: &[u8],
+    ) -> Result<SignatureStatus, CollectorError> {
+        with_signature_file(signature, |_, path| {
+            let (found, principals) = run(
+                Command::new("ssh-keygen").args(["-Y", "find-principals", "-f", allowed_signers, "-s", path]),
+                b"",
+            )?;
+            let principal = match principals.lines().next() {
+                Some(principal) if found => principal.to_string(),
+                _ => return Ok(SignatureStatus::UnknownKey),
+            };
+            let (verified, _) = run(
+                Command::new("ssh-keygen").args([
+                    "-Y",
+                    "verify",
+                    "-f",
+                    allowed_signers,
+                    "-I",
+                    &principal,
+                    "-n",
+                    "git",
+                    "-s",
+                    path,
+                ]),
+                payload,
+            )?;
+            if verified {
+                Ok(SignatureStatus::Good(principal))
+            } else {
+                Ok(SignatureStatus::Bad)
+            }
+        })
+    }
+}
+
+/// new directory only the user can access, the name is never reused even if a file exists there
+fn create_private_dir() -> Result<PathBuf, CollectorError> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    for _ in 0..16 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let path = env::temp_dir().join(format!(
+            "tamatebako-{}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos
+        ));
+        match DirBuilder::new().mode(0o700).create(&path) {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(CollectorError::Io(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "fail to create temporary directory",
+    )))
+}
+
+/// write the signature into a private temporary directory while `f` runs,
+/// the tools read it only from a file. `f` gets (directory, signature file).
+fn with_signature_file<T>(
+    signature: &[u8],
+    f: impl FnOnce(&Path, &str) -> Result<T, CollectorError>,
+) -> Result<T, CollectorError> {
+    let dir = create_private_dir()?;
+    let path = dir.join("signature");
+    let result = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)
+        .and_then(|mut file| file.write_all(signature))
+        .map_err(CollectorError::from)
+        .and_then(|_| f(&dir, path.to_str().unwrap_or_default()));
+    if let Err(e) = fs::remove_dir_all(&dir) {
+        warn!("remove temporary directory error: {:?}", e);
+    }
+    result
+}
+
+/// (exit status is success, stdout) of the command with `input` as stdin
+fn run(command: &mut Command, input: &[u8]) -> Result<(bool, String), CollectorError> {
+    debug!("run {:?}", command);
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        debug!("{:?}: {}", output.status, String::from_utf8_lossy(&output.stderr));
+    }
+    Ok((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    ))
+}
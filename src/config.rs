@@ -4,6 +4,8 @@ use std::io;
 use std::io::Read;
 use std::path::PathBuf;
 
+use crate::version::VersionScheme;
+
 fn default_rootdir() -> PathBuf {
     PathBuf::from(format!(
         "{}/.tamatebako",
@@ -23,7 +25,25 @@ pub struct Config {
     #[serde(default = "default_rootdir")]
     pub rootdir: PathBuf,
     pub git_ssh_key: Option<String>,
+    /// environment variable name of the passphrase of `git_ssh_key`
+    pub git_ssh_key_passphrase_env: Option<String>,
     pub github_access_token: Option<String>,
+    /// GitHub Enterprise Server API (`https://github.example.com/api/v3`)
+    pub github_api_url: Option<String>,
+    /// number of repositories in one GraphQL query, 0 disables GraphQL batch mode
+    pub github_graphql_batch_size: Option<usize>,
+    /// seconds to sleep at most for GitHub rate limit reset, projects are skipped when it takes longer
+    pub github_rate_limit_max_wait: Option<u64>,
+    #[serde(default)]
+    pub gitlab: HashMap<String, HostConfig>,
+    #[serde(default)]
+    pub gitea: HashMap<String, HostConfig>,
+    /// container registries, `token` is the password of `username`
+    #[serde(default)]
+    pub oci: HashMap<String, HostConfig>,
+    /// default registry url keyed by source type (`crate`, ...)
+    #[serde(default)]
+    pub registry: HashMap<String, String>,
     #[serde(rename = "project")]
     pub projects: HashMap<String, ProjectConfig>,
 }
@@ -33,19 +53,116 @@ pub struct ProjectConfig {
     pub url: String,
     pub source: Option<ProjectSourceConfig>,
     pub version_regex: Option<String>,
+    /// the latest version is the greatest version of the scheme instead of the newest one
+    pub version_scheme: Option<VersionScheme>,
+}
+
+/// API endpoint of a self-hosted service, keyed by host name
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct HostConfig {
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub token: Option<String>,
+}
+
+impl HostConfig {
+    pub fn base_url(&self, host: &str) -> String {
+        match &self.url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("https://{}", host),
+        }
+    }
 }
 
 impl Config {
     pub fn get_database_url(&self) -> String {
         format!("{}/tamatebako.sqlite", self.rootdir.to_str().unwrap())
     }
+
+    /// registry url of the project source, falls back to `[registry]` and then `default`
+    pub fn registry_url(&self, source: &ProjectSourceConfig, default: &str) -> String {
+        let url = match (&source.registry, source.source_type()) {
+            (Some(url), _) => url.as_str(),
+            (None, Some(t)) => self.registry.get(t).map(|s| s.as_str()).unwrap_or(default),
+            (None, None) => default,
+        };
+        url.trim_end_matches('/').to_string()
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ProjectSourceConfig {
     pub git: Option<String>,
     pub branch: Option<String>,
+    /// collect only the tags reachable from `branch` (git source)
+    pub reachable_only: Option<bool>,
+    /// branch name patterns (`release-*`), tags reachable from each branch are collected into its channel
+    pub branches: Option<Vec<String>>,
+    /// `clone` (default), `bare` (shallow bare mirror) or `remote` (list remote tags without clone)
+    pub git_mode: Option<String>,
+    /// ssh private key of the git source, overrides `git_ssh_key`
+    pub ssh_key: Option<String>,
+    /// environment variable name of the passphrase of the ssh key
+    pub ssh_key_passphrase_env: Option<String>,
+    /// user name of the git source, the token is used as its password over https
+    pub username: Option<String>,
+    /// gpg keyring of the keys trusted to sign the tags (git source)
+    pub gpg_keyring: Option<String>,
+    /// ssh allowed signers file of the keys trusted to sign the tags (git source)
+    pub allowed_signers: Option<String>,
     pub github: Option<String>,
+    pub gitlab: Option<String>,
+    pub gitea: Option<String>,
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+    pub pypi: Option<String>,
+    pub npm: Option<String>,
+    pub gomod: Option<String>,
+    pub image: Option<String>,
+    pub maven: Option<String>,
+    pub feed: Option<String>,
+    pub http: Option<String>,
+    pub jsonpath: Option<String>,
+    pub date_jsonpath: Option<String>,
+    pub selector: Option<String>,
+    pub date_selector: Option<String>,
+    pub registry: Option<String>,
+    pub api_url: Option<String>,
+    /// access token of the source, overrides the global or per host token
+    pub token: Option<String>,
+}
+
+impl ProjectSourceConfig {
+    /// key of the collector registered for this source
+    pub fn source_type(&self) -> Option<&'static str> {
+        if self.git.is_some() {
+            Some("git")
+        } else if self.github.is_some() {
+            Some("github")
+        } else if self.gitlab.is_some() {
+            Some("gitlab")
+        } else if self.gitea.is_some() {
+            Some("gitea")
+        } else if self.crate_name.is_some() {
+            Some("crate")
+        } else if self.pypi.is_some() {
+            Some("pypi")
+        } else if self.npm.is_some() {
+            Some("npm")
+        } else if self.gomod.is_some() {
+            Some("gomod")
+        } else if self.image.is_some() {
+            Some("image")
+        } else if self.maven.is_some() {
+            Some("maven")
+        } else if self.feed.is_some() {
+            Some("feed")
+        } else if self.http.is_some() {
+            Some("http")
+        } else {
+            None
+        }
+    }
 }
 
 pub fn load_config(path: &str) -> io::Result<Config> {
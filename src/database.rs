@@ -12,11 +12,53 @@ mod schema {
             version -> Text,
             bump_date -> Timestamp,
             url -> Nullable<Text>,
+            yanked -> Bool,
+        }
+    }
+
+    table! {
+        http_cache (project_name, url) {
+            project_name -> Text,
+            url -> Text,
+            etag -> Nullable<Text>,
+            last_modified -> Nullable<Text>,
+        }
+    }
+
+    table! {
+        release_notes (project_name, version) {
+            project_name -> Text,
+            version -> Text,
+            notes -> Text,
+        }
+    }
+
+    table! {
+        version_changes (project_name, channel, version) {
+            project_name -> Text,
+            channel -> Text,
+            version -> Text,
+            previous_version -> Text,
+            commits -> Integer,
+            contributors -> Integer,
+            files_changed -> Integer,
+            insertions -> Integer,
+            deletions -> Integer,
+        }
+    }
+
+    table! {
+        tag_signatures (project_name, version) {
+            project_name -> Text,
+            version -> Text,
+            status -> Text,
+            signer -> Nullable<Text>,
+            tag_oid -> Nullable<Text>,
         }
     }
 }
 
-use self::schema::version_history;
+use self::schema::{version_changes, version_history};
 
 #[derive(Deserialize, Insertable, QueryableByName)]
 #[diesel(table_name = version_history)]
@@ -35,6 +77,28 @@ pub struct VersionHistory {
     pub version: String,
     pub bump_date: NaiveDateTime,
     pub url: Option<String>,
+    pub yanked: bool,
+}
+
+/// commits and diffstat since the previous version of the channel
+#[derive(Insertable, Queryable, Debug)]
+#[diesel(table_name = version_changes)]
+pub struct VersionChanges {
+    pub project_name: String,
+    pub channel: String,
+    pub version: String,
+    pub previous_version: String,
+    pub commits: i32,
+    pub contributors: i32,
+    pub files_changed: i32,
+    pub insertions: i32,
+    pub deletions: i32,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    count: i64,
 }
 
 pub fn get_database_connection(url: &str) -> SqliteConnection {
@@ -49,12 +113,92 @@ channel TEXT,
 version TEXT,
 bump_date TIMESTAMP,
 url TEXT,
+yanked BOOLEAN NOT NULL DEFAULT 0,
 UNIQUE (project_name, channel, version)
 )";
     match sql_query(SQL_STMT).execute(conn) {
         Ok(_) => {}
         Err(e) => error!("create table error. {:?}", e),
     };
+
+    const HTTP_CACHE_SQL_STMT: &str = "CREATE TABLE IF NOT EXISTS http_cache (
+project_name TEXT NOT NULL,
+url TEXT NOT NULL,
+etag TEXT,
+last_modified TEXT,
+PRIMARY KEY (project_name, url)
+)";
+    if let Err(e) = sql_query(HTTP_CACHE_SQL_STMT).execute(conn) {
+        error!("create table error. {:?}", e);
+    }
+
+    const RELEASE_NOTES_SQL_STMT: &str = "CREATE TABLE IF NOT EXISTS release_notes (
+project_name TEXT NOT NULL,
+version TEXT NOT NULL,
+notes TEXT NOT NULL,
+PRIMARY KEY (project_name, version)
+)";
+    if let Err(e) = sql_query(RELEASE_NOTES_SQL_STMT).execute(conn) {
+        error!("create table error. {:?}", e);
+    }
+
+    const VERSION_CHANGES_SQL_STMT: &str = "CREATE TABLE IF NOT EXISTS version_changes (
+project_name TEXT NOT NULL,
+channel TEXT NOT NULL,
+version TEXT NOT NULL,
+previous_version TEXT NOT NULL,
+commits INTEGER NOT NULL,
+contributors INTEGER NOT NULL,
+files_changed INTEGER NOT NULL,
+insertions INTEGER NOT NULL,
+deletions INTEGER NOT NULL,
+PRIMARY KEY (project_name, channel, version)
+)";
+    if let Err(e) = sql_query(VERSION_CHANGES_SQL_STMT).execute(conn) {
+        error!("create table error. {:?}", e);
+    }
+
+    const TAG_SIGNATURES_SQL_STMT: &str = "CREATE TABLE IF NOT EXISTS tag_signatures (
+project_name TEXT NOT NULL,
+version TEXT NOT NULL,
+status TEXT NOT NULL,
+signer TEXT,
+tag_oid TEXT,
+PRIMARY KEY (project_name, version)
+)";
+    if let Err(e) = sql_query(TAG_SIGNATURES_SQL_STMT).execute(conn) {
+        error!("create table error. {:?}", e);
+    }
+
+    // migrate tables created by older versions
+    add_column_if_not_exists(conn, "version_history", "yanked", "BOOLEAN NOT NULL DEFAULT 0");
+    add_column_if_not_exists(conn, "tag_signatures", "tag_oid", "TEXT");
+}
+
+fn add_column_if_not_exists(conn: &mut SqliteConnection, table: &str, column: &str, definition: &str) {
+    use diesel::sql_types::Text;
+
+    let exists = match sql_query(format!(
+        "SELECT COUNT(*) AS count FROM pragma_table_info('{}') WHERE name = ?",
+        table
+    ))
+    .bind::<Text, _>(column)
+    .get_result::<CountRow>(conn)
+    {
+        Ok(row) => row.count > 0,
+        Err(e) => {
+            error!("table info error. {:?}", e);
+            return;
+        }
+    };
+    if exists {
+        return;
+    }
+
+    info!("add column {}.{}", table, column);
+    if let Err(e) = sql_query(format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition)).execute(conn) {
+        error!("alter table error. {:?}", e);
+    }
 }
 
 #[allow(dead_code)]
@@ -74,6 +218,16 @@ pub fn have_version_history(conn: &mut SqliteConnection, i_name: &str, i_channel
     }
 }
 
+/// (channel, version) of the stored versions of the project
+pub fn get_project_versions(conn: &mut SqliteConnection, i_name: &str) -> QueryResult<Vec<(String, String)>> {
+    use self::schema::version_history::dsl::*;
+
+    version_history
+        .filter(project_name.eq(i_name))
+        .select((channel, version))
+        .load::<(String, String)>(conn)
+}
+
 pub fn insert_version_history(conn: &mut SqliteConnection, input: &VersionHistory) -> QueryResult<usize> {
     use self::schema::version_history::dsl::*;
 
@@ -84,18 +238,212 @@ pub fn insert_version_history(conn: &mut SqliteConnection, input: &VersionHistor
             version.eq(input.version.clone()),
             bump_date.eq(input.bump_date),
             url.eq(input.url.clone()),
+            yanked.eq(input.yanked),
         ))
         .execute(conn)
 }
 
+pub fn update_yanked(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_channel: &str,
+    i_version: &str,
+    i_yanked: bool,
+) -> QueryResult<usize> {
+    use self::schema::version_history::dsl::*;
+
+    diesel::update(
+        version_history
+            .filter(project_name.eq(i_name))
+            .filter(channel.eq(i_channel))
+            .filter(version.eq(i_version))
+            .filter(yanked.ne(i_yanked)),
+    )
+    .set(yanked.eq(i_yanked))
+    .execute(conn)
+}
+
+/// (etag, last_modified) of the last successful response of the url
+pub fn get_http_cache(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_url: &str,
+) -> QueryResult<Option<(Option<String>, Option<String>)>> {
+    use self::schema::http_cache::dsl::*;
+
+    http_cache
+        .filter(project_name.eq(i_name))
+        .filter(url.eq(i_url))
+        .select((etag, last_modified))
+        .first::<(Option<String>, Option<String>)>(conn)
+        .optional()
+}
+
+pub fn save_http_cache(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_url: &str,
+    i_etag: Option<&str>,
+    i_last_modified: Option<&str>,
+) -> QueryResult<usize> {
+    use self::schema::http_cache::dsl::*;
+
+    diesel::replace_into(http_cache)
+        .values((
+            project_name.eq(i_name),
+            url.eq(i_url),
+            etag.eq(i_etag),
+            last_modified.eq(i_last_modified),
+        ))
+        .execute(conn)
+}
+
+pub fn delete_http_cache(conn: &mut SqliteConnection, i_name: &str, i_url: &str) -> QueryResult<usize> {
+    use self::schema::http_cache::dsl::*;
+
+    diesel::delete(http_cache.filter(project_name.eq(i_name)).filter(url.eq(i_url))).execute(conn)
+}
+
+/// versions of the project (and the channel) which are not yanked
+pub fn get_available_versions(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_channel: Option<&str>,
+) -> QueryResult<Vec<VersionHistory>> {
+    use self::schema::version_history::dsl::*;
+
+    let mut query = version_history
+        .filter(project_name.eq(i_name))
+        .filter(yanked.eq(false))
+        .into_boxed();
+    if let Some(i_channel) = i_channel {
+        query = query.filter(channel.eq(i_channel));
+    }
+    query.load::<VersionHistory>(conn)
+}
+
+/// stored rows of the version, one per channel
+pub fn get_versions(conn: &mut SqliteConnection, i_name: &str, i_version: &str) -> QueryResult<Vec<VersionHistory>> {
+    use self::schema::version_history::dsl::*;
+
+    version_history
+        .filter(project_name.eq(i_name))
+        .filter(version.eq(i_version))
+        .order(channel.asc())
+        .load::<VersionHistory>(conn)
+}
+
+pub fn get_release_notes(conn: &mut SqliteConnection, i_name: &str, i_version: &str) -> QueryResult<Option<String>> {
+    use self::schema::release_notes::dsl::*;
+
+    release_notes
+        .filter(project_name.eq(i_name))
+        .filter(version.eq(i_version))
+        .select(notes)
+        .first::<String>(conn)
+        .optional()
+}
+
+/// (version, notes) of the project
+pub fn get_project_release_notes(conn: &mut SqliteConnection, i_name: &str) -> QueryResult<Vec<(String, String)>> {
+    use self::schema::release_notes::dsl::*;
+
+    release_notes
+        .filter(project_name.eq(i_name))
+        .select((version, notes))
+        .load::<(String, String)>(conn)
+}
+
+pub fn save_release_notes(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_version: &str,
+    i_notes: &str,
+) -> QueryResult<usize> {
+    use self::schema::release_notes::dsl::*;
+
+    diesel::replace_into(release_notes)
+        .values((project_name.eq(i_name), version.eq(i_version), notes.eq(i_notes)))
+        .execute(conn)
+}
+
+pub fn get_version_changes(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_channel: &str,
+    i_version: &str,
+) -> QueryResult<Option<VersionChanges>> {
+    use self::schema::version_changes::dsl::*;
+
+    version_changes
+        .filter(project_name.eq(i_name))
+        .filter(channel.eq(i_channel))
+        .filter(version.eq(i_version))
+        .first::<VersionChanges>(conn)
+        .optional()
+}
+
+pub fn save_version_changes(conn: &mut SqliteConnection, input: &VersionChanges) -> QueryResult<usize> {
+    diesel::replace_into(version_changes::table).values(input).execute(conn)
+}
+
+/// (status, signer) of the signed tag of the version
+pub fn get_tag_signature(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_version: &str,
+) -> QueryResult<Option<(String, Option<String>)>> {
+    use self::schema::tag_signatures::dsl::*;
+
+    tag_signatures
+        .filter(project_name.eq(i_name))
+        .filter(version.eq(i_version))
+        .select((status, signer))
+        .first::<(String, Option<String>)>(conn)
+        .optional()
+}
+
+/// (version, tag object id) of the project whose tag signature is already verified
+pub fn get_verified_tags(conn: &mut SqliteConnection, i_name: &str) -> QueryResult<Vec<(String, Option<String>)>> {
+    use self::schema::tag_signatures::dsl::*;
+
+    tag_signatures
+        .filter(project_name.eq(i_name))
+        .select((version, tag_oid))
+        .load::<(String, Option<String>)>(conn)
+}
+
+pub fn save_tag_signature(
+    conn: &mut SqliteConnection,
+    i_name: &str,
+    i_version: &str,
+    i_status: &str,
+    i_signer: Option<&str>,
+    i_tag_oid: &str,
+) -> QueryResult<usize> {
+    use self::schema::tag_signatures::dsl::*;
+
+    diesel::replace_into(tag_signatures)
+        .values((
+            project_name.eq(i_name),
+            version.eq(i_version),
+            status.eq(i_status),
+            signer.eq(i_signer),
+            tag_oid.eq(i_tag_oid),
+        ))
+        .execute(conn)
+}
+
+/// the latest version of each project, or of each (project, channel) when `per_channel` is true
 pub fn get_latest_version_history(
     conn: &mut SqliteConnection,
     order_by: Option<String>,
     is_order_by_desc: bool,
+    per_channel: bool,
 ) -> Vec<VersionHistory> {
     use self::schema::version_history::dsl::*;
     use diesel::dsl::sql;
-    use diesel::sql_types::{Integer, Nullable, Text, Timestamp};
+    use diesel::sql_types::{Bool, Integer, Nullable, Text, Timestamp};
 
     let order_by_str = if is_order_by_desc { "DESC" } else { "ASC" };
 
@@ -104,15 +452,23 @@ pub fn get_latest_version_history(
         None => "project_name".to_string(),
     };
 
-    let version_histories = sql::<(Integer, Text, Text, Text, Timestamp, Nullable<Text>)>(
+    let same_channel = if per_channel {
+        "AND vh.channel = vh2.channel"
+    } else {
+        ""
+    };
+
+    // versions with the same bump_date (remote git mode, ...) are ordered by id, the last stored one is the latest
+    let version_histories = sql::<(Integer, Text, Text, Text, Timestamp, Nullable<Text>, Bool)>(
         format!(
             "SELECT * FROM version_history AS vh
-  WHERE NOT EXISTS (
+  WHERE NOT vh.yanked AND NOT EXISTS (
     SELECT 1 FROM version_history AS vh2
-      WHERE vh.project_name = vh2.project_name AND vh.bump_date < vh2.bump_date
+      WHERE vh.project_name = vh2.project_name {} AND NOT vh2.yanked
+        AND (vh.bump_date < vh2.bump_date OR (vh.bump_date = vh2.bump_date AND vh.id < vh2.id))
   )
   ORDER BY vh.{} {};",
-            order_by_key, order_by_str
+            same_channel, order_by_key, order_by_str
         )
         .as_str(),
     )
@@ -121,10 +477,17 @@ pub fn get_latest_version_history(
     let mut ret: Vec<VersionHistory> = vec![];
     for vv in version_histories.iter() {
         for v in vv {
+            let mut query = version_history
+                .filter(project_name.eq(v.project_name.to_string()))
+                .filter(yanked.eq(false))
+                .into_boxed();
+            if per_channel {
+                query = query.filter(channel.eq(v.channel.to_string()));
+            }
             ret.push(
-                version_history
-                    .filter(project_name.eq(v.project_name.to_string()))
+                query
                     .order(bump_date.desc())
+                    .then_order_by(id.desc())
                     .limit(1)
                     .first::<VersionHistory>(conn)
                     .unwrap(),
@@ -16,6 +16,7 @@ use std::{env, fs};
 mod collector;
 mod config;
 mod database;
+mod version;
 mod web;
 
 #[derive(Parser)]
@@ -37,7 +38,13 @@ struct Cli {
 #[derive(Subcommand)]
 enum SubCommand {
     /// check and store version history information
-    Check {},
+    Check {
+        #[arg(
+            long = "no-cache",
+            help = "ignore ETag / Last-Modified of the last check, e.g. after changing version_regex"
+        )]
+        no_cache: bool,
+    },
 
     /// output the latest version of each projects
     List {
@@ -45,6 +52,30 @@ enum SubCommand {
         sort_key: Option<ListSortKey>,
         #[arg(short = 'r', long = "reverse", help = "reverse the order of the sort item")]
         reverse: bool,
+        #[arg(
+            short = 'a',
+            long = "all-channels",
+            help = "output the latest version of each channel"
+        )]
+        all_channels: bool,
+    },
+
+    /// output the stored information and release notes of the version
+    Show {
+        #[arg(help = "project name")]
+        project: String,
+        #[arg(help = "version")]
+        version: String,
+    },
+
+    /// output the shortlog between two versions of the git source
+    Diff {
+        #[arg(help = "project name")]
+        project: String,
+        #[arg(help = "older version")]
+        from: String,
+        #[arg(help = "newer version")]
+        to: String,
     },
 
     /// serve version history visualize web application
@@ -58,6 +89,43 @@ enum ListSortKey {
     DateTime,
 }
 
+/// replace the latest version of the projects with `version_scheme` by the greatest version
+fn latest_by_version_scheme(
+    conn: &mut diesel::SqliteConnection,
+    config: &config::Config,
+    version_histories: &mut [database::VersionHistory],
+    per_channel: bool,
+) {
+    for version_history in version_histories.iter_mut() {
+        let scheme = match config
+            .projects
+            .get(&version_history.project_name)
+            .and_then(|p| p.version_scheme)
+        {
+            Some(scheme) => scheme,
+            None => continue,
+        };
+        let channel = if per_channel {
+            Some(version_history.channel.as_str())
+        } else {
+            None
+        };
+        let versions = match database::get_available_versions(conn, &version_history.project_name, channel) {
+            Ok(versions) => versions,
+            Err(e) => {
+                error!("select error: {:?}", e);
+                continue;
+            }
+        };
+        let latest = versions
+            .into_iter()
+            .max_by(|a, b| version::compare(Some(scheme), &a.version, &b.version).then(a.bump_date.cmp(&b.bump_date)));
+        if let Some(latest) = latest {
+            *version_history = latest;
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), reqwest::Error> {
     let cli = Cli::parse();
@@ -108,15 +176,33 @@ async fn main() -> Result<(), reqwest::Error> {
         SubCommand::Web {} => {
             web::serve();
         }
-        SubCommand::List { sort_key, reverse } => {
+        SubCommand::List {
+            sort_key,
+            reverse,
+            all_channels,
+        } => {
             let order_by = match sort_key {
                 Some(ListSortKey::Name) => "project_name",
                 Some(ListSortKey::Version) => "version",
                 Some(ListSortKey::DateTime) => "bump_date",
                 _ => "project_name",
             };
-            let version_histories =
-                database::get_latest_version_history(&mut dbconn, Some(order_by.to_string()), reverse);
+            let mut version_histories =
+                database::get_latest_version_history(&mut dbconn, Some(order_by.to_string()), reverse, all_channels);
+            latest_by_version_scheme(&mut dbconn, &config, &mut version_histories, all_channels);
+            // the latest versions may be replaced, and versions are not ordered as strings
+            let version_scheme =
+                |v: &database::VersionHistory| config.projects.get(&v.project_name).and_then(|p| p.version_scheme);
+            match sort_key {
+                Some(ListSortKey::Version) => version_histories.sort_by(|a, b| {
+                    version::compare_mixed((version_scheme(a), &a.version), (version_scheme(b), &b.version))
+                }),
+                Some(ListSortKey::DateTime) => version_histories.sort_by_key(|v| v.bump_date),
+                _ => {}
+            }
+            if reverse && matches!(sort_key, Some(ListSortKey::Version) | Some(ListSortKey::DateTime)) {
+                version_histories.reverse();
+            }
             let mut name_max_len = 0;
             for version_history in &version_histories {
                 if name_max_len < version_history.project_name.len() {
@@ -124,71 +210,157 @@ async fn main() -> Result<(), reqwest::Error> {
                 }
             }
             for version_history in &version_histories {
+                let channel = if all_channels && !version_history.channel.is_empty() {
+                    format!(" [{}]", version_history.channel)
+                } else {
+                    "".to_string()
+                };
                 println!(
-                    "{name:>width$}: {version:<10} ({date})",
+                    "{name:>width$}: {version:<10} ({date}){channel}",
                     name = version_history.project_name,
                     width = name_max_len,
                     version = version_history.version,
-                    date = version_history.bump_date
+                    date = version_history.bump_date,
+                    channel = channel
                 );
             }
         }
-        SubCommand::Check {} => {
+        SubCommand::Show { project, version } => {
+            let version_histories = match database::get_versions(&mut dbconn, &project, &version) {
+                Ok(v) => v,
+                Err(e) => {
+                    error!("get versions error: {:?}", e);
+                    return Ok(());
+                }
+            };
+            if version_histories.is_empty() {
+                println!("not found: {} {}", project, version);
+                return Ok(());
+            }
+            for version_history in &version_histories {
+                let channel = if version_history.channel.is_empty() {
+                    "".to_string()
+                } else {
+                    format!(" [{}]", version_history.channel)
+                };
+                let yanked = if version_history.yanked { " (yanked)" } else { "" };
+                println!(
+                    "{name} {version}{channel} ({date}){yanked}",
+                    name = version_history.project_name,
+                    version = version_history.version,
+                    channel = channel,
+                    date = version_history.bump_date,
+                    yanked = yanked
+                );
+                if let Some(url) = &version_history.url {
+                    println!("  {}", url);
+                }
+                match database::get_version_changes(
+                    &mut dbconn,
+                    &project,
+                    &version_history.channel,
+                    &version_history.version,
+                ) {
+                    Ok(Some(changes)) => println!("  {}", collector::ChangeSummary::from(changes)),
+                    Ok(None) => {}
+                    Err(e) => error!("get version changes error: {:?}", e),
+                }
+            }
+            match database::get_tag_signature(&mut dbconn, &project, &version) {
+                Ok(Some((status, signer))) => match collector::SignatureStatus::from_columns(&status, signer) {
+                    Some(status) => println!("  signature: {}", status),
+                    None => error!("unknown signature status: {}", status),
+                },
+                Ok(None) => {}
+                Err(e) => error!("get tag signature error: {:?}", e),
+            }
+            match database::get_release_notes(&mut dbconn, &project, &version) {
+                Ok(Some(notes)) => println!("\n{}", notes),
+                Ok(None) => {}
+                Err(e) => error!("get release notes error: {:?}", e),
+            }
+        }
+        SubCommand::Diff { project, from, to } => {
+            let (project_config, source) = match config.projects.get(&project) {
+                Some(project_config) => match &project_config.source {
+                    Some(source) if source.git.is_some() => (project_config, source),
+                    _ => {
+                        error!("{} is not a git source", project);
+                        return Ok(());
+                    }
+                },
+                None => {
+                    error!("not found project: {}", project);
+                    return Ok(());
+                }
+            };
+            let ctx = collector::CollectorContext::new(&config, true);
+            let diff = collector::git::GitCollector::from_config(&ctx, &project, project_config, source)
+                .and_then(|c| c.diff(&from, &to));
+            let (changes, shortlog) = match diff {
+                Ok(diff) => diff,
+                Err(e) => {
+                    error!("{}: {}", project, e);
+                    return Ok(());
+                }
+            };
+            for (author, summaries) in &shortlog {
+                println!("{} ({}):", author, summaries.len());
+                for summary in summaries {
+                    println!("      {}", summary);
+                }
+                println!();
+            }
+            println!("{}", changes);
+        }
+        SubCommand::Check { no_cache } => {
+            let registry = collector::Registry::default();
+            let ctx = collector::CollectorContext::new(&config, !no_cache);
+            let mut rate_limited_projects = vec![];
             for (project_name, project) in &config.projects {
                 debug!("config.project: {:?}", project);
 
-                match project.source {
+                let mut project_collector = match registry.build(&ctx, project_name, project) {
+                    Some(Ok(c)) => c,
+                    Some(Err(e)) => {
+                        error!("{}: {}", project_name, e);
+                        continue;
+                    }
                     None => continue,
-                    Some(_) => {}
-                }
+                };
 
-                let mut new_release_versions = 0;
-                let source = project.source.clone().unwrap();
-
-                debug!("config.source.git: {:?}", source.git);
-                if let Some(git) = source.git {
-                    let branch = match source.branch {
-                        Some(b) => b,
-                        None => "master".to_string(),
-                    };
-                    let mut git_collector = collector::git::GitCollector::new(
-                        &db_url,
-                        config.rootdir.to_str().unwrap(),
-                        &git,
-                        project_name,
-                        &project.url,
-                        &branch,
-                        &project.version_regex,
-                        config.git_ssh_key.clone(),
-                    );
-                    git_collector.init();
-
-                    // get version info
-                    new_release_versions = git_collector.collect();
-                }
-
-                debug!("config.source.github: {:?}", source.github);
-                if let Some(github_repo) = source.github {
-                    let tmp: Vec<&str> = github_repo.split('/').collect();
-                    let owner = tmp[0];
-                    let repo = tmp[1];
-                    let github_collector = collector::github::GitHubCollector::new(
-                        &db_url,
-                        project_name,
-                        owner,
-                        repo,
-                        config.github_access_token.clone(),
-                    );
-                    match github_collector.get_releases().await {
-                        Ok(n) => new_release_versions = n,
-                        Err(e) => error!("github collector error: {:#?}", e),
+                let new_release_versions = match project_collector.collect(&ctx).await {
+                    Ok(releases) => {
+                        let n = collector::store(&mut dbconn, project_name, &releases);
+                        ctx.save_validators(&mut dbconn, project_name);
+                        n
                     }
-                }
+                    Err(collector::CollectorError::RateLimited(e)) => {
+                        warn!("{} is skipped. {}", project_name, e);
+                        ctx.discard_validators(project_name);
+                        rate_limited_projects.push(project_name.as_str());
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("{} collector error: {}", project_name, e);
+                        ctx.discard_validators(project_name);
+                        continue;
+                    }
+                };
 
                 if new_release_versions == 0 {
                     info!("not exist new version(s): {}", project_name);
                 }
             }
+
+            if !rate_limited_projects.is_empty() {
+                rate_limited_projects.sort();
+                warn!(
+                    "{} project(s) skipped because of rate limit: {}",
+                    rate_limited_projects.len(),
+                    rate_limited_projects.join(", ")
+                );
+            }
         }
     }
     Ok(())
@@ -0,0 +1,449 @@
+use regex::Regex;
+use std::cmp::Ordering;
+
+lazy_static! {
+    // https://peps.python.org/pep-0440/#appendix-b-parsing-version-strings-with-regular-expressions
+    static ref RE_PEP440: Regex = Regex::new(
+        r"(?ix)^\s*v?
+        (?:(?P<epoch>[0-9]+)!)?
+        (?P<release>[0-9]+(?:\.[0-9]+)*)
+        (?:[-_.]?(?P<pre_l>alpha|a|beta|b|preview|pre|c|rc)[-_.]?(?P<pre_n>[0-9]+)?)?
+        (?:-(?P<post_n1>[0-9]+)|[-_.]?(?P<post_l>post|rev|r)[-_.]?(?P<post_n2>[0-9]+)?)?
+        (?:[-_.]?(?P<dev_l>dev)[-_.]?(?P<dev_n>[0-9]+)?)?
+        (?:\+(?P<local>[a-z0-9]+(?:[-_.][a-z0-9]+)*))?
+        \s*$"
+    )
+    .unwrap();
+    static ref RE_CALVER: Regex =
+        Regex::new(r"^v?(?P<year>[0-9]{4}|[0-9]{2})(?P<rest>(?:[._-][0-9]+)+)(?P<modifier>.*)$").unwrap();
+}
+
+/// how the versions of a project are ordered, `version_scheme` of the project
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionScheme {
+    /// `1.2.3-rc.1+build`, `v` prefix and missing minor / patch are allowed
+    Semver,
+    /// python packages (`1!2.0.post1`, `1.0rc1`, `2.0.dev3`)
+    Pep440,
+    /// `[epoch:]upstream[-revision]` compared like dpkg, `~` sorts before anything
+    Debian,
+    /// `YYYY.MM.DD`, `YY.MM.MICRO`, ... with an optional modifier (`2024.04-rc1`)
+    Calver,
+}
+
+/// dot separated identifier, numeric identifiers have lower precedence
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+/// missing part of a version, lower or higher than any value
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Bound<T> {
+    NegativeInfinity,
+    Value(T),
+    Infinity,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SemVer {
+    core: (u64, u64, u64),
+    pre: Vec<Identifier>,
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // pre-release version has lower precedence than the normal version
+        self.core
+            .cmp(&other.core)
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn parse_semver(version: &str) -> Option<SemVer> {
+    let version = version.trim_start_matches(['v', 'V']);
+    let version = version.split_once('+').map_or(version, |(v, _)| v);
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let mut numbers = [0; 3];
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        numbers[i] = part.parse().ok()?;
+    }
+
+    let mut identifiers = vec![];
+    for identifier in pre.into_iter().flat_map(|p| p.split('.')) {
+        if identifier.is_empty() || !identifier.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+            return None;
+        }
+        identifiers.push(match identifier.parse() {
+            Ok(n) => Identifier::Numeric(n),
+            Err(_) => Identifier::Alphanumeric(identifier.to_string()),
+        });
+    }
+    Some(SemVer {
+        core: (numbers[0], numbers[1], numbers[2]),
+        pre: identifiers,
+    })
+}
+
+/// segment of the local version label, numbers are greater than strings
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LocalSegment {
+    String(String),
+    Number(u64),
+}
+
+/// (epoch, release, pre, post, dev, local) in the order of `packaging.version`
+type Pep440Key = (
+    u64,
+    Vec<u64>,
+    Bound<(u8, u64)>,
+    Bound<u64>,
+    Bound<u64>,
+    Bound<Vec<LocalSegment>>,
+);
+
+fn parse_pep440(version: &str) -> Option<Pep440Key> {
+    let caps = RE_PEP440.captures(version)?;
+    let number = |name: &str| caps.name(name).and_then(|m| m.as_str().parse::<u64>().ok());
+
+    let epoch = number("epoch").unwrap_or(0);
+    let mut release: Vec<u64> = caps["release"].split('.').filter_map(|n| n.parse().ok()).collect();
+    // 1.0 == 1.0.0
+    while release.len() > 1 && release.last() == Some(&0) {
+        release.pop();
+    }
+    let pre = caps.name("pre_l").map(|l| {
+        let kind = match l.as_str().to_lowercase().as_str() {
+            "a" | "alpha" => 0,
+            "b" | "beta" => 1,
+            _ => 2,
+        };
+        (kind, number("pre_n").unwrap_or(0))
+    });
+    let post = if caps.name("post_n1").is_some() || caps.name("post_l").is_some() {
+        Some(number("post_n1").or_else(|| number("post_n2")).unwrap_or(0))
+    } else {
+        None
+    };
+    let dev = caps.name("dev_l").map(|_| number("dev_n").unwrap_or(0));
+    let local = caps.name("local").map(|l| {
+        l.as_str()
+            .split(['-', '_', '.'])
+            .map(|s| match s.parse() {
+                Ok(n) => LocalSegment::Number(n),
+                Err(_) => LocalSegment::String(s.to_lowercase()),
+            })
+            .collect()
+    });
+
+    let pre = match (pre, post, dev) {
+        // 1.0.dev0 comes before 1.0a0
+        (None, None, Some(_)) => Bound::NegativeInfinity,
+        (None, _, _) => Bound::Infinity,
+        (Some(pre), _, _) => Bound::Value(pre),
+    };
+    let post = post.map_or(Bound::NegativeInfinity, Bound::Value);
+    let dev = dev.map_or(Bound::Infinity, Bound::Value);
+    let local = local.map_or(Bound::NegativeInfinity, Bound::Value);
+    Some((epoch, release, pre, post, dev, local))
+}
+
+/// (epoch, upstream, revision) of a Debian version
+fn parse_debian(version: &str) -> Option<(u64, &str, &str)> {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((epoch, rest)) => (epoch.parse().ok()?, rest),
+        None => (0, version),
+    };
+    let (upstream, revision) = match rest.rsplit_once('-') {
+        Some((upstream, revision)) => (upstream, revision),
+        None => (rest, ""),
+    };
+    if !upstream.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((epoch, upstream, revision))
+}
+
+/// weight of a non-digit character in dpkg, `~` before the end and letters before the others
+fn dpkg_order(c: Option<u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(b'~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// `verrevcmp` of dpkg, alternate non-digit and digit parts are compared
+fn dpkg_compare(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let is_digit = |s: &[u8], i: usize| s.get(i).is_some_and(|c| c.is_ascii_digit());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !is_digit(a, i)) || (j < b.len() && !is_digit(b, j)) {
+            let (ac, bc) = (dpkg_order(a.get(i).copied()), dpkg_order(b.get(j).copied()));
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_diff = Ordering::Equal;
+        while is_digit(a, i) && is_digit(b, j) {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if is_digit(a, i) {
+            return Ordering::Greater;
+        }
+        if is_digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+    Ordering::Equal
+}
+
+fn compare_debian(a: (u64, &str, &str), b: (u64, &str, &str)) -> Ordering {
+    a.0.cmp(&b.0)
+        .then_with(|| dpkg_compare(a.1, b.1))
+        .then_with(|| dpkg_compare(a.2, b.2))
+}
+
+/// (numbers, modifier) of a calendar version, the version without modifier is the newest
+fn parse_calver(version: &str) -> Option<(Vec<u64>, Bound<Vec<Identifier>>)> {
+    let caps = RE_CALVER.captures(version)?;
+    let mut numbers = vec![caps["year"].parse().ok()?];
+    for number in caps["rest"].split(['.', '_', '-']).filter(|n| !n.is_empty()) {
+        numbers.push(number.parse().ok()?);
+    }
+    let modifier = caps["modifier"].trim_start_matches(['.', '_', '-']);
+    let modifier = if modifier.is_empty() {
+        Bound::Infinity
+    } else {
+        Bound::Value(natural_key(modifier))
+    };
+    Some((numbers, modifier))
+}
+
+/// runs of digits and of the other characters
+fn natural_key(version: &str) -> Vec<Identifier> {
+    let mut key = vec![];
+    let mut rest = version;
+    while let Some(c) = rest.chars().next() {
+        let is_digit = c.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != is_digit)
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        key.push(match run.parse() {
+            Ok(n) if is_digit => Identifier::Numeric(n),
+            _ => Identifier::Alphanumeric(run.to_string()),
+        });
+        rest = tail;
+    }
+    key
+}
+
+impl VersionScheme {
+    /// order of two versions, `None` when either is not a version of the scheme
+    pub fn compare(self, a: &str, b: &str) -> Option<Ordering> {
+        match self {
+            VersionScheme::Semver => Some(parse_semver(a)?.cmp(&parse_semver(b)?)),
+            VersionScheme::Pep440 => Some(parse_pep440(a)?.cmp(&parse_pep440(b)?)),
+            VersionScheme::Debian => Some(compare_debian(parse_debian(a)?, parse_debian(b)?)),
+            VersionScheme::Calver => Some(parse_calver(a)?.cmp(&parse_calver(b)?)),
+        }
+    }
+
+    pub fn is_valid(self, version: &str) -> bool {
+        match self {
+            VersionScheme::Semver => parse_semver(version).is_some(),
+            VersionScheme::Pep440 => parse_pep440(version).is_some(),
+            VersionScheme::Debian => parse_debian(version).is_some(),
+            VersionScheme::Calver => parse_calver(version).is_some(),
+        }
+    }
+}
+
+/// order of versions by the scheme, versions which the scheme can not parse come first.
+/// without scheme, digits are compared as numbers (`1.9` < `1.10`).
+pub fn compare(scheme: Option<VersionScheme>, a: &str, b: &str) -> Ordering {
+    let scheme = match scheme {
+        Some(scheme) => scheme,
+        None => return natural_key(a).cmp(&natural_key(b)),
+    };
+    match (scheme.is_valid(a), scheme.is_valid(b)) {
+        (true, true) => scheme.compare(a, b).unwrap_or(Ordering::Equal),
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => natural_key(a).cmp(&natural_key(b)),
+    }
+}
+
+/// order of versions of the projects with different schemes. versions are grouped by the scheme,
+/// versions without scheme first, because the orders of the schemes do not agree with each other.
+pub fn compare_mixed(a: (Option<VersionScheme>, &str), b: (Option<VersionScheme>, &str)) -> Ordering {
+    a.0.cmp(&b.0).then_with(|| compare(a.0, a.1, b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// each version is less than the next one
+    fn assert_ascending(scheme: Option<VersionScheme>, versions: &[&str]) {
+        for pair in versions.windows(2) {
+            assert_eq!(
+                compare(scheme, pair[0], pair[1]),
+                Ordering::Less,
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+            assert_eq!(
+                compare(scheme, pair[1], pair[0]),
+                Ordering::Greater,
+                "{} > {}",
+                pair[1],
+                pair[0]
+            );
+        }
+    }
+
+    #[test]
+    fn natural_order_compares_numbers() {
+        assert_ascending(None, &["1.2", "1.9", "1.10", "2.0"]);
+    }
+
+    #[test]
+    fn semver_prerelease_precedence() {
+        assert_ascending(
+            Some(VersionScheme::Semver),
+            &[
+                "1.0.0-alpha",
+                "1.0.0-alpha.1",
+                "1.0.0-alpha.beta",
+                "1.0.0-beta",
+                "1.0.0-beta.2",
+                "1.0.0-beta.11",
+                "1.0.0-rc.1",
+                "1.0.0",
+                "v1.9.0",
+                "1.10.0",
+            ],
+        );
+        assert_eq!(
+            compare(Some(VersionScheme::Semver), "1.0.0+build.1", "1.0.0+build.2"),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn pep440_dev_pre_post_and_epoch() {
+        assert_ascending(
+            Some(VersionScheme::Pep440),
+            &[
+                "1.0.dev0",
+                "1.0a0",
+                "1.0a2.dev1",
+                "1.0a2",
+                "1.0b1",
+                "1.0rc1",
+                "1.0",
+                "1.0+abc",
+                "1.0+5",
+                "1.0.post1.dev1",
+                "1.0.post1",
+                "1.1",
+                "1!0.1",
+            ],
+        );
+        assert_eq!(compare(Some(VersionScheme::Pep440), "1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(
+            compare(Some(VersionScheme::Pep440), "1.0-1", "1.0.post1"),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn debian_tilde_revision_and_epoch() {
+        assert_ascending(
+            Some(VersionScheme::Debian),
+            &[
+                "1.0~~", "1.0~~a", "1.0~rc1", "1.0", "1.0-1", "1.0-2", "1.0-10", "1.0a", "1.1", "1:0.1",
+            ],
+        );
+        assert_eq!(compare(Some(VersionScheme::Debian), "1.01", "1.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn calver_modifier_comes_before_release() {
+        assert_ascending(
+            Some(VersionScheme::Calver),
+            &["2024.1-rc1", "2024.1", "2024.4", "2024.10", "2024.10.1", "2025.01"],
+        );
+    }
+
+    #[test]
+    fn versions_out_of_scheme_come_first() {
+        assert!(!VersionScheme::Semver.is_valid("latest"));
+        assert_eq!(compare(Some(VersionScheme::Semver), "latest", "0.0.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn mixed_schemes_are_totally_ordered() {
+        let semver = Some(VersionScheme::Semver);
+        let mut versions = vec![(semver, "1.0.0"), (None, "1.0.0-a"), (semver, "1.0.0-rc.1")];
+        for a in versions.iter() {
+            for b in versions.iter() {
+                for c in versions.iter() {
+                    if compare_mixed(*a, *b).is_le() && compare_mixed(*b, *c).is_le() {
+                        assert!(compare_mixed(*a, *c).is_le(), "{:?} <= {:?} <= {:?}", a, b, c);
+                    }
+                }
+            }
+        }
+        versions.sort_by(|a, b| compare_mixed(*a, *b));
+        assert_eq!(
+            versions,
+            vec![(None, "1.0.0-a"), (semver, "1.0.0-rc.1"), (semver, "1.0.0")]
+        );
+    }
+}